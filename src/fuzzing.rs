@@ -1,12 +1,10 @@
-use crate::{Config, runner, SearchReport};
-use crate::map::Map;
+use treesearch::{Config, runner, SearchReport, Map};
 use std::{thread};
 use std::marker::PhantomData;
-use std::time::SystemTime;
 
 const THREADS: usize = 12;
 const RUNS_PER_THREAD: usize = 5_000_000;
-const METHODS: &'static [&'static str; 6] = &["BFS", "DFS", "GBFS", "AS", "CUS1", "CUS2"];
+const METHODS: &[&str; 6] = &["BFS", "DFS", "GBFS", "AS", "CUS1", "CUS2"];
 
 pub fn discrete_fuzzing_unit(config: &Config) {
 	let map = {
@@ -14,23 +12,18 @@ pub fn discrete_fuzzing_unit(config: &Config) {
 		Map::random_maze(rows, cols, config.target_count())
 	};
 
-	let result: Vec<(SearchReport, _)> = METHODS.iter()
+	let result: Vec<SearchReport> = METHODS.iter()
 		.map(|method| {
-			let mut map = map.clone();
 			let mut config = config.clone();
 			config.method = method.to_string();
 
-			let past = SystemTime::now();
-			let res = runner(&mut map, &config).unwrap();
-
-			let elapsed_ns = SystemTime::now().duration_since(past).unwrap().as_nanos();
-			(res, elapsed_ns)
+			runner(&map, &config).unwrap()
 		})
 		.collect();
 
 	result.iter()
-		.map(|(x, _)| x.solution.is_some())
-		.fold_first(|acc, x| {
+		.map(|x| x.solution.is_some())
+		.reduce(|acc, x| {
 			if acc != x {
 				eprintln!("Disagreement on whether there was a solution on map:");
 				eprintln!("{}", map);
@@ -39,23 +32,21 @@ pub fn discrete_fuzzing_unit(config: &Config) {
 		});
 
 	let timings: Vec<_> = result.iter()
-		.map(|(_, x)| format!("{}", x))
+		.map(|x| format!("{}", x.elapsed.as_nanos()))
 		.collect();
 	print!("{}, ", timings.join(", "));
 
 	let nodes: Vec<_> = result.iter()
-		.map(|(x, _)| x.search_nodes)
+		.map(|x| x.search_nodes)
 		.map(|x| format!("{}", x))
 		.collect();
 	print!("{}, ", nodes.join(", "));
 
-	let path_lens: Vec<_> = result.into_iter()
-		.map(|(x, _)| x.solution)
-		.filter(|x| x.is_some())
-		.map(|x| x.unwrap_or(vec![]))
-		.map(|x| format!("{}", x.len()))
+	let path_costs: Vec<_> = result.into_iter()
+		.filter_map(|x| x.cost)
+		.map(|x| format!("{}", x))
 		.collect();
-	println!("{}", path_lens.join(", "));
+	println!("{}", path_costs.join(", "));
 }
 
 pub fn wrapper(config: Config) {
@@ -0,0 +1,77 @@
+#![cfg_attr(all(test, feature = "nightly"), feature(test))]
+#![allow(clippy::needless_return)]
+
+//! This is my Assignment 1 code for COS20019 - Introduction to Artificial Intelligence
+//!
+//! In the codebase below, I implemented various search algorithms as requested in the assignment specifications.
+//! The algorithms are exposed as a library so that they can be driven without going through the command line:
+//!
+//! ```
+//! use std::str::FromStr;
+//! use treesearch::{Map, CellType, Method};
+//!
+//! let map: Map<CellType> = Map::from_str("[2, 3]\n(0, 0)\n(2, 1)\n(1, 0, 1, 1)").unwrap();
+//! let report = Method::AStar.build(&map, false).search();
+//!
+//! assert_eq!(report.solution.map(|path| path.len()), Some(3));
+//! ```
+//!
+//! For basic usage of the command line interface, please consult README.md
+
+#[cfg(all(test, feature = "nightly"))]
+extern crate test;
+#[cfg(feature = "eyecandy")]
+extern crate ansi_term;
+extern crate rand;
+
+/// Shorthand for boxing a [`GraphSearch`](search::graph_search::GraphSearch) with the default queue and filter,
+/// or with the cost table in place of the filter if `cost_table` is tacked on at the end,
+/// and `weigher = ...` after that for moves weighed other than by the default of the cursor's weigher
+macro_rules! graph_search {
+	($map:expr, $heu:expr, $cursor:ty) => {{
+		Box::new(graph_search!(@init $map, $heu, $cursor))
+	}};
+	($map:expr, $heu:expr, $cursor:ty, cost_table) => {{
+		Box::new(graph_search!(@init $map, $heu, $cursor).with_cost_table())
+	}};
+	($map:expr, $heu:expr, $cursor:ty, weigher = $weigher:expr) => {{
+		Box::new(graph_search!(@init $map, $heu, $cursor).with_weigher($weigher))
+	}};
+	($map:expr, $heu:expr, $cursor:ty, cost_table, weigher = $weigher:expr) => {{
+		Box::new(graph_search!(@init $map, $heu, $cursor).with_cost_table().with_weigher($weigher))
+	}};
+	(@init $map:expr, $heu:expr, $cursor:ty) => {{
+		let heu = $heu;
+
+		$crate::search::graph_search::GraphSearch::init(
+			$map,
+			heu,
+			$crate::utils::queue::sorted::SortedQueue::init::<$cursor>(),
+			$crate::utils::filter::global_duped,
+		)
+	}};
+}
+
+pub mod map;
+pub mod search;
+pub mod utils;
+pub mod runner;
+pub mod report;
+pub mod tour;
+pub mod scenario;
+
+pub use crate::map::{Map, CellType};
+pub use crate::map::movement::{Movement, Corners};
+pub use crate::map::parse::{MapParseError, MapParseErrors};
+pub use crate::map::validate::{MapIssue, Severity};
+pub use crate::map::format::MapFormat;
+pub use crate::map::ascii::Legend;
+pub use crate::map::occupancy::{Occupancy, MapServer, InvalidImage};
+pub use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight, CustomMoveWeight, EuclideanMoveWeight};
+pub use crate::search::steps::SearchEvent;
+pub use crate::search::limits::{SearchLimits, Limit};
+pub use crate::runner::{Config, Method, HeuristicKind, runner, heuristic_check};
+pub use crate::utils::visited::Visited;
+pub use crate::report::SearchReport;
+pub use crate::tour::{Tour, plan_tour};
+pub use crate::scenario::{Scenario, Outcome, Bucket, read_scenarios, run_scenario};
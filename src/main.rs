@@ -1,91 +1,41 @@
-#![feature(test)]
-#![feature(iterator_fold_self)]
-
-//! This is my Assignment 1 code for COS20019 - Introduction to Artificial Intelligence
+//! Command line interface to the treesearch library
 //!
-//! In the codebase below, I implemented various search algorithms as requested in the assignment specifications.
 //! For basic usage, please consult README.md
 
-#[cfg(test)]
-extern crate test;
-#[cfg(feature = "eyecandy")]
-extern crate ansi_term;
-extern crate rand;
-
-use crate::map::{Map, CellType};
+use treesearch::{Config, Movement, Corners, CustomMoveWeight, HeuristicKind, MapFormat, Legend, CellType};
 use std::error::Error;
 use std::env;
-use rand::random;
-use crate::search::dfs::DepthFirst;
-use crate::search::bfs::BreadthFirst;
-use crate::search::{Search, UniformMoveWeight, CustomMoveWeight, Direction};
-use crate::search::gbfs::GBFCursor;
-use crate::utils::heuristics::{DefaultHeuristic, CustomManhattan, ManhattanHeuristic};
-use crate::utils::queue::sorted::SortedQueue;
-use crate::search::graph_search::GraphSearch;
-use crate::search::astar::AStarCursor;
-use crate::search::wastar::WeightedASCursor;
-use crate::utils::filter;
-use crate::search::dijkstra::DijkstraCursor;
+use std::time::Duration;
 
 #[cfg(not(feature = "fuzzing"))]
-use std::{fs, mem, str::FromStr};
-use crate::search::iddfs::IterativeDeepening;
+use std::{fs, collections::HashMap, path::{Path, PathBuf}, str::FromStr};
+#[cfg(not(feature = "fuzzing"))]
+use treesearch::{runner, heuristic_check, SearchReport, Map, Severity, Method, MapServer, read_scenarios, run_scenario};
+#[cfg(not(feature = "fuzzing"))]
+use treesearch::scenario::buckets;
 
 #[cfg(feature = "fuzzing")]
 mod fuzzing;
 
-macro_rules! graph_search {
-	($map:expr, $heu:expr, $cursor:ty) => {{
-		let heu = $heu;
-
-		Box::new(GraphSearch::init(
-			$map,
-			heu,
-			SortedQueue::init::<$cursor>(),
-			filter::global_duped,
-		))
-	}}
-}
-
-mod map;
-mod search;
-mod utils;
-
-#[derive(Clone, Default)]
-pub struct Config {
-	// The input map file, can be RANDOM
-	pub map_file: String,
-	// Search method, refer to docs
-	pub method: String,
-	// Where to copy the map to as a save, useful for testing with random
-	pub save_map: Option<String>,
-	// Variable move weight experiment (ie. the weights of moving in different direction changes)
-	pub var_move_wt: bool,
-	// The follow relates to randomly generated maps
-	pub rand_size: Option<(usize, usize)>,
-	pub target_count: Option<usize>,
-}
-
-impl Config {
-	fn rand_size(&self) -> (usize, usize) {
-		self.rand_size.clone().unwrap_or((random::<usize>() % 16 + 16, random::<usize>() % 112 + 16))
-	}
-
-	fn target_count(&self) -> usize {
-		self.target_count.clone().unwrap_or(2)
-	}
-}
-
 /// This is the application entry point, it accepts arguments in the form of `executable <maze_file> <search algorithm>`
 fn main() -> Result<(), Box<dyn Error>> {
 	let mut args = env::args().skip(1).peekable();
 
 	let mut config = Config::default();
 
+	// Checks the heuristic over the map instead of searching it
+	#[cfg(not(feature = "fuzzing"))]
+	let check = args.next_if(|x| x == "check-heuristic").is_some();
+	// Runs the method over every scenario of a Moving AI Lab benchmark instead
+	#[cfg(not(feature = "fuzzing"))]
+	let scenario = !check && args.next_if(|x| x == "scenario").is_some();
+
 	while let Some(arg) = args.peek() {
 		match arg.to_lowercase().as_ref() {
 			"--variable-move-weight" => config.var_move_wt = true,
+			"--strict" => config.strict = true,
+			"--cost-table" => config.cost_table = true,
+			"--visit-all" => config.visit_all = true,
 			"--map-size" => {
 				args.next().unwrap(); // Advance the iterator since we've peeked above
 
@@ -108,6 +58,135 @@ fn main() -> Result<(), Box<dyn Error>> {
 					.expect("the targets count is not a number");
 				config.target_count = Some(count)
 			}
+			"--max-nodes" => {
+				args.next().unwrap();
+
+				let count = args.peek()
+					.expect("please specify the number of nodes after --max-nodes")
+					.parse::<usize>()
+					.expect("the nodes count is not a number");
+				config.max_nodes = Some(count)
+			}
+			"--max-depth" => {
+				args.next().unwrap();
+
+				let depth = args.peek()
+					.expect("please specify the depth after --max-depth")
+					.parse::<usize>()
+					.expect("the depth is not a number");
+				config.max_depth = Some(depth)
+			}
+			"--timeout" => {
+				args.next().unwrap();
+
+				let seconds = args.peek()
+					.expect("please specify the number of seconds after --timeout")
+					.parse::<f64>()
+					.expect("the timeout is not a number");
+				config.timeout = Some(Duration::from_secs_f64(seconds))
+			}
+			"--movement" => {
+				args.next().unwrap();
+
+				let movement = args.peek()
+					.expect("please specify four, eight, knight or hex after --movement")
+					.parse::<Movement>()?;
+				// Keep the corner rule if it was given first
+				config.movement = match (movement, config.movement) {
+					(Movement::EightWay(_), Movement::EightWay(corners)) => Movement::EightWay(corners),
+					(movement, _) => movement
+				}
+			}
+			"--corners" => {
+				args.next().unwrap();
+
+				let corners = args.peek()
+					.expect("please specify cut, no-squeeze or avoid after --corners")
+					.parse::<Corners>()?;
+				config.movement = Movement::EightWay(corners)
+			}
+			"--terrain" => {
+				args.next().unwrap();
+
+				let patches = args.peek()
+					.expect("please specify the number of patches after --terrain")
+					.parse::<usize>()
+					.expect("the patches count is not a number");
+				config.terrain = Some(patches)
+			}
+			"--move-weights" => {
+				args.next().unwrap();
+
+				let weights = args.peek()
+					.expect("please specify the weights after --move-weights, as in up=4,left=3,down=1,right=2")
+					.parse::<CustomMoveWeight>()?;
+				config.move_weights = Some(weights);
+				config.var_move_wt = true
+			}
+			"--heuristic" => {
+				args.next().unwrap();
+
+				let heuristic = args.peek()
+					.expect("please specify manhattan, euclidean, octile, chebyshev, zero, landmark or exact after --heuristic")
+					.parse::<HeuristicKind>()?;
+				config.heuristic = Some(heuristic)
+			}
+			"--weight" => {
+				args.next().unwrap();
+
+				let weight = args.peek()
+					.expect("please specify how much to inflate the heuristic by after --weight")
+					.parse::<f64>()
+					.expect("the weight is not a number");
+				if !weight.is_finite() || weight < 1.0 {
+					return Err(format!("the weight has to be a number from 1 up, got {}", weight).into());
+				}
+				config.weight = Some(weight)
+			}
+			"--landmark-file" => {
+				args.next().unwrap();
+				config.landmark_file = Some(args.peek().expect("please specify the file to keep the landmark tables in").to_owned())
+			}
+			"--format" => {
+				args.next().unwrap();
+				config.format = args.peek().expect("please specify rect or ascii after --format").parse::<MapFormat>()?
+			}
+			"--save-format" => {
+				args.next().unwrap();
+				config.save_format = Some(args.peek().expect("please specify rect or ascii after --save-format").parse::<MapFormat>()?)
+			}
+			"--legend" => {
+				args.next().unwrap();
+				config.legend = args.peek()
+					.expect("please specify the characters after --legend, as in initial=S,target=G,wall=#,blank=_")
+					.parse::<Legend>()?
+			}
+			"--start" | "--goal" => {
+				let start = arg == "--start";
+				args.next().unwrap();
+
+				let x = args.next()
+					.expect("please specify the x and y in metres after --start and --goal")
+					.parse::<f64>()
+					.expect("x is not a number");
+				let y = args.peek()
+					.expect("please specify the x and y in metres after --start and --goal")
+					.parse::<f64>()
+					.expect("y is not a number");
+				if start {
+					config.start = Some((x, y));
+				} else {
+					config.goals.push((x, y));
+				}
+			}
+			"--unknown" => {
+				args.next().unwrap();
+				config.unknown = Some(match args.peek().expect("please specify wall or free after --unknown").to_ascii_lowercase().as_ref() {
+					"wall" => CellType::Wall(false),
+					"free" => CellType::Blank(false),
+					x => return Err(format!("unknown cells can be a wall or free, not {}", x).into())
+				})
+			}
 			"--save-map" => {
 				args.next().unwrap();
 				config.save_map = Some(args.peek().expect("please specify the file name to save the map to").to_owned())
@@ -124,95 +203,219 @@ fn main() -> Result<(), Box<dyn Error>> {
 	#[cfg(not(feature = "fuzzing"))]
 		{
 			config.map_file = args.next().expect("test file required");
+			if check {
+				let map = load_map(&config);
+				let check = heuristic_check(&map, &config)?;
+
+				println!("{}", check);
+				if !check.admissible() || !check.consistent() {
+					std::process::exit(1);
+				}
+				return Ok(());
+			}
+
 			config.method = args.next().expect("test method required").to_ascii_uppercase();
-			let mut map: Map<CellType> = if config.map_file.trim() == "RANDOM" {
-				let (rows, cols) = config.rand_size();
-				Map::random_maze(rows, cols, config.target_count())
-			} else {
-				Map::from_str(
-					&fs::read_to_string(&config.map_file)
-						.expect("test file cannot be found")
-				)
-					.expect("failed to parse test file")
-			};
+			if scenario {
+				return run_benchmark(&config, args.next());
+			}
+
+			let mut map = load_map(&config);
 
 			if let Some(path) = &config.save_map {
-				map.save(path).expect("failed to copy map");
+				map.save_as(path, config.save_format.unwrap_or(config.format), &config.legend).expect("failed to copy map");
+			}
+
+			if let Some(warning) = config.heuristic_warning(&map) {
+				eprintln!("Warning: {}", warning);
 			}
 
-			let _ = runner(&mut map, &config)?;
+			let report = runner(&map, &config)?;
+			print_report(&mut map, &config, &report);
 		}
-	return Ok(());
+	Ok(())
 }
 
-pub struct SearchReport {
-	pub search_nodes: usize,
-	pub solution: Option<Vec<Direction>>,
-}
+/// Reads or generates the map of the [`Config`], and puts the options that go with the map onto it
+#[cfg(not(feature = "fuzzing"))]
+fn load_map(config: &Config) -> Map<CellType> {
+	let mut map: Map<CellType> = if config.map_file.trim() == "RANDOM" {
+		let (rows, cols) = config.rand_size();
+		Map::random_maze(rows, cols, config.target_count())
+	} else if config.map_file.ends_with(".yaml") || config.map_file.ends_with(".yml") {
+		load_map_server(config).unwrap_or_else(|e| {
+			eprintln!("{}: {}", config.map_file, e);
+			std::process::exit(1);
+		})
+	} else {
+		let source = fs::read_to_string(&config.map_file).expect("test file cannot be found");
 
-pub fn runner(map: &mut Map<CellType>, config: &Config) -> Result<SearchReport, Box<dyn Error>> {
-	let mut implementation: Box<dyn Search> = match config.method.to_ascii_uppercase().as_ref() {
-		"DFS" => Box::new(DepthFirst::init(map)),
-		"BFS" => Box::new(BreadthFirst::init(map)),
-		"GBFS" => {
-			if config.var_move_wt {
-				graph_search!(map, CustomManhattan::init(&map), GBFCursor<CustomMoveWeight>)
-			} else {
-				graph_search!(map, ManhattanHeuristic::init(&map), GBFCursor<UniformMoveWeight>)
+		match Map::parse_as(&source, config.format, &config.legend) {
+			Ok(map) => map,
+			// Every mistake in the file, pointed at like a compiler would
+			Err(errors) => {
+				for error in &errors.0 {
+					eprintln!("{}:{}", config.map_file, error);
+				}
+				std::process::exit(1);
 			}
 		}
-		"AS" | "ASTAR" => {
-			if config.var_move_wt {
-				graph_search!(map, CustomManhattan::init(&map), AStarCursor<CustomMoveWeight>)
-			} else {
-				graph_search!(map, ManhattanHeuristic::init(&map), AStarCursor<UniformMoveWeight>)
+	};
+
+	map.movement = config.movement;
+	if let Some(weights) = config.move_weights {
+		map.weights = weights;
+	}
+	if let Some(patches) = config.terrain {
+		// Up to 5 times the cost of plain ground
+		map.scatter_terrain(patches, 5);
+	}
+
+	// Errors would trip up the searches, warnings only stop the run with --strict
+	let issues = map.validate();
+	for issue in &issues {
+		eprintln!("{}: {}: {}", config.map_file, issue.severity(), issue);
+	}
+	if issues.iter().any(|x| config.strict || x.severity() == Severity::Error) {
+		std::process::exit(1);
+	}
+
+	map
+}
+
+/// Reads the occupancy grid of a ROS `map_server` YAML file, with the initial cell and targets where the [`Config`] puts them in world metres
+#[cfg(not(feature = "fuzzing"))]
+fn load_map_server(config: &Config) -> Result<Map<CellType>, Box<dyn Error>> {
+	let mut server = match MapServer::from_str(&fs::read_to_string(&config.map_file)?) {
+		Ok(x) => x,
+		Err(errors) => {
+			for error in &errors.0 {
+				eprintln!("{}:{}", config.map_file, error);
 			}
+			std::process::exit(1);
 		}
-		"CUS1" | "IDDFS" => {
-			Box::new(IterativeDeepening::init(map))
-		}
-		"CUS2" | "WAS" | "WASTAR" | "WEIGHTED_ASTAR" => {
-			if config.var_move_wt {
-				graph_search!(map, CustomManhattan::init(&map), WeightedASCursor<CustomMoveWeight>)
-			} else {
-				graph_search!(map, ManhattanHeuristic::init(&map), WeightedASCursor<UniformMoveWeight>)
-			}
+	};
+	if let Some(unknown) = config.unknown {
+		server.occupancy.unknown = unknown;
+	}
+
+	let image = Path::new(&config.map_file).parent().unwrap_or_else(|| Path::new("")).join(&server.image);
+	let map = Map::from_pnm(&fs::read(&image).map_err(|e| format!("can't read the image {}: {}", image.display(), e))?, &server.occupancy)?;
+
+	let start = config.start.ok_or("please give the start in metres with --start")?;
+	if config.goals.is_empty() {
+		return Err("please give at least one goal in metres with --goal".into());
+	}
+
+	// Each point has to be on open ground
+	let place = |point: (f64, f64)| -> Result<(usize, usize), Box<dyn Error>> {
+		let cell = server.cell(&map, point).ok_or_else(|| format!("({}, {}) is off the map", point.0, point.1))?;
+		if let CellType::Wall(_) = map.read_cell(cell) {
+			return Err(format!("({}, {}) is in cell {:?}, which isn't free", point.0, point.1, cell).into());
 		}
-		"UCS" | "DIJKSTRA" => {
-			if config.var_move_wt {
-				graph_search!(map, DefaultHeuristic::default(), DijkstraCursor<CustomMoveWeight>)
-			} else {
-				graph_search!(map, DefaultHeuristic::default(), DijkstraCursor<UniformMoveWeight>)
+		Ok(cell)
+	};
+
+	let goals = config.goals.iter().map(|x| place(*x)).collect::<Result<Vec<_>, _>>()?;
+	let mut map = map.between(place(start)?, goals[0]);
+	for goal in &goals[1..] {
+		map.targets.push(*goal);
+		*map.read_cell_mut(*goal) = CellType::Target;
+	}
+
+	Ok(map)
+}
+
+/// Runs the method over every scenario in the `.scen` file of the [`Config`], and prints the totals of each bucket
+///
+/// The maps are looked for where the scenarios say, from the folder of the `.scen` file, then next to it, unless there's a `map_file` for all of them.
+/// Exits with 1 if a method that's meant to find the cheapest path doesn't come out at the recorded length.
+#[cfg(not(feature = "fuzzing"))]
+fn run_benchmark(config: &Config, map_file: Option<String>) -> Result<(), Box<dyn Error>> {
+	let source = fs::read_to_string(&config.map_file)?;
+	let scenarios = match read_scenarios(&source) {
+		Ok(x) => x,
+		Err(errors) => {
+			for error in &errors.0 {
+				eprintln!("{}:{}", config.map_file, error);
 			}
-		}
-		x => {
-			panic!("unrecognized search method: {}", x)
+			std::process::exit(1);
 		}
 	};
 
-	let report = implementation.search();
+	let folder = Path::new(&config.map_file).parent().unwrap_or_else(|| Path::new(""));
+	let mut maps: HashMap<String, Map<CellType>> = HashMap::new();
+	let mut outcomes = vec![];
+	let mut mismatched = 0;
 
-	#[cfg(not(feature = "fuzzing"))] {
-		mem::drop(implementation);
+	for (i, scenario) in scenarios.iter().enumerate() {
+		if !maps.contains_key(&scenario.map) {
+			let path = match &map_file {
+				Some(x) => PathBuf::from(x),
+				None => vec![folder.join(&scenario.map), folder.join(Path::new(&scenario.map).file_name().unwrap_or_default())].into_iter()
+					.find(|x| x.is_file())
+					.ok_or_else(|| format!("can't find the map {} of the scenarios", scenario.map))?
+			};
 
-		println!("{} {} {}", config.map_file, config.method, report.search_nodes);
-		if let Some(path) = &report.solution {
-			println!("{}", path.iter()
-				.map(|d| format!("{}; ", d))
-				.fold(String::new(), |x, d| {
-					x + &d
-				})
-				.trim()
-			);
+			match Map::from_movingai(&fs::read_to_string(&path)?) {
+				Ok(map) => maps.insert(scenario.map.clone(), map),
+				Err(errors) => {
+					for error in &errors.0 {
+						eprintln!("{}:{}", path.display(), error);
+					}
+					std::process::exit(1);
+				}
+			};
+		}
 
-			#[cfg(feature = "eyecandy")]
-				map.draw_path(&path);
-		} else {
-			println!("No solution found.");
+		let outcome = run_scenario(&maps[&scenario.map], scenario, config)
+			.map_err(|e| format!("scenario {} on line {}: {}", i + 1, scenario.line, e))?;
+		if !outcome.matches() {
+			mismatched += 1;
+			eprintln!("Scenario {} on line {} from {:?} to {:?} came out at {} rather than {}", i + 1, scenario.line, scenario.start, scenario.goal,
+				outcome.length.map_or("no path".to_owned(), |x| format!("{:.4}", x)), scenario.optimal);
 		}
+		outcomes.push(outcome);
+	}
+
+	println!("{:>6} {:>6} {:>6} {:>8} {:>12} {:>12}", "bucket", "runs", "solved", "matched", "mean nodes", "mean time");
+	for bucket in buckets(&outcomes) {
+		println!("{:>6} {:>6} {:>6} {:>8} {:>12.1} {:>12.2?}", bucket.bucket, bucket.runs, bucket.solved, bucket.matched, bucket.mean_nodes(), bucket.mean_elapsed());
+	}
+
+	if mismatched > 0 && Method::from_str(&config.method)?.finds_cheapest() {
+		eprintln!("{} of {} scenarios didn't come out at the recorded length", mismatched, outcomes.len());
+		std::process::exit(1);
+	}
+	Ok(())
+}
+
+/// Writes the outcome of the search to stdout, and the visualisation of the map to stderr
+#[cfg(not(feature = "fuzzing"))]
+fn print_report(map: &mut Map<CellType>, config: &Config, report: &SearchReport) {
+	map.mark_visits(&report.visited);
+
+	println!("{} {} {}", config.map_file, config.method, report.search_nodes);
+	if let Some(path) = &report.solution {
+		println!("{}", path.iter()
+			.map(|d| format!("{}; ", d))
+			.fold(String::new(), |x, d| {
+				x + &d
+			})
+			.trim()
+		);
 
 		#[cfg(feature = "eyecandy")]
-		eprintln!("{}", map);
+			map.draw_path(path);
+	} else if let Some(limit) = report.cut_off {
+		println!("Search cut off: {}.", limit);
+	} else {
+		println!("No solution found.");
 	}
-	Ok(report)
+
+	if let Some(bound) = report.suboptimality {
+		eprintln!("Costs at most {:.2} times the cheapest path{}", bound, report.cut_off.map(|x| format!(", {}", x)).unwrap_or_default());
+	}
+
+	#[cfg(feature = "eyecandy")]
+	eprintln!("{}", map);
 }
@@ -0,0 +1,203 @@
+use crate::map::{Map, CellType};
+use crate::map::movement::Movement;
+use crate::map::parse::{MapParseError, MapParseErrors};
+use crate::search::CustomMoveWeight;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+/// The characters standing for each kind of cell in the ASCII art format
+///
+/// The digits 1 to 9 are always open ground costing that many times over, and spaces are always open ground too,
+/// so that trailing spaces taken off by text editors don't matter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Legend {
+	pub initial: char,
+	pub target: char,
+	pub wall: char,
+	pub blank: char,
+}
+
+/// The same characters as the [`Debug`](std::fmt::Debug) renderer of the map, except open ground is a `.` to be seen in a text editor
+impl Default for Legend {
+	fn default() -> Self {
+		Legend {
+			initial: 'I',
+			target: 'T',
+			wall: 'X',
+			blank: '.',
+		}
+	}
+}
+
+/// Error for when the legend can't be made sense of
+#[derive(Debug)]
+pub struct InvalidLegend(pub String);
+
+impl Display for InvalidLegend {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid legend: {}", self.0)
+	}
+}
+
+impl Error for InvalidLegend {}
+
+/// Reads comma separated `cell=character` pairs, such as `initial=S,target=G,wall=#,blank=_`
+///
+/// Cells left out keep their default character
+impl FromStr for Legend {
+	type Err = InvalidLegend;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut legend = Legend::default();
+
+		for pair in s.split(',').filter(|x| !x.trim().is_empty()) {
+			let (cell, character) = pair.split_once('=')
+				.ok_or_else(|| InvalidLegend(format!("expected cell=character, got {}", pair.trim())))?;
+			// Spaces are kept, they're fine for open ground
+			let mut chars = character.chars();
+			let character = match (chars.next(), chars.next()) {
+				(Some(x), None) => x,
+				_ => return Err(InvalidLegend(format!("{} should be a single character", character)))
+			};
+			if character.is_ascii_digit() {
+				return Err(InvalidLegend(format!("{} is kept for terrain costs", character)));
+			}
+
+			match cell.trim().to_ascii_lowercase().as_ref() {
+				"initial" => legend.initial = character,
+				"target" => legend.target = character,
+				"wall" => legend.wall = character,
+				"blank" => legend.blank = character,
+				x => return Err(InvalidLegend(format!("unknown cell {}", x)))
+			}
+		}
+
+		let mut used = [legend.initial, legend.target, legend.wall, legend.blank];
+		used.sort_unstable();
+		if used.windows(2).any(|x| x[0] == x[1]) || (legend.blank != ' ' && used.contains(&' ')) {
+			return Err(InvalidLegend("every cell needs a character of its own".to_owned()));
+		}
+
+		Ok(legend)
+	}
+}
+
+impl Map<CellType> {
+	/// Reads a map drawn as a grid of characters, one line per row, with the cells going by the `legend`
+	///
+	/// Like the rectangle format, the map can open with a `weights:` line. Short lines are made up with open ground,
+	/// and the targets are listed in reading order. Every mistake is returned, same as [`Map::from_str`].
+	pub fn from_ascii(s: &str, legend: &Legend) -> Result<Map<CellType>, MapParseErrors> {
+		let mut lines: Vec<(usize, &str)> = s.lines().enumerate().map(|(i, x)| (i + 1, x)).collect();
+		while lines.last().is_some_and(|x| x.1.trim().is_empty()) {
+			lines.pop();
+		}
+		let end = lines.last().map_or(1, |x| x.0 + 1);
+		let mut errors = vec![];
+
+		let mut weights = CustomMoveWeight::default();
+		if lines.first().is_some_and(|x| x.1.trim_start().starts_with("weights:")) {
+			let (number, text) = lines.remove(0);
+			let at = text.find("weights:").expect("just checked") + "weights:".len();
+			match text[at..].parse::<CustomMoveWeight>() {
+				Ok(x) => weights = x,
+				Err(e) => errors.push(MapParseError {
+					line: number,
+					column: text[..at].chars().count() + 1,
+					token: text[at..].trim().to_owned(),
+					reason: e.to_string(),
+				})
+			}
+		}
+
+		let rows = lines.len();
+		let cols = lines.iter().map(|x| x.1.chars().count()).max().unwrap_or(0);
+		if rows == 0 || cols == 0 {
+			errors.push(MapParseError { line: end, column: 1, token: String::new(), reason: "missing the grid".to_owned() });
+			return Err(MapParseErrors(errors));
+		}
+
+		let mut map = Map {
+			rows,
+			cols,
+			initial: (0, 0),
+			targets: vec![],
+			movement: Movement::default(),
+			weights,
+			values: vec![CellType::Blank(false); rows * cols],
+			terrain: vec![1; rows * cols],
+		};
+		let mut initial = None;
+
+		for (y, (number, text)) in lines.into_iter().enumerate() {
+			for (x, c) in text.chars().enumerate() {
+				let i = map.index((x, y));
+				let error = |reason: String| MapParseError { line: number, column: x + 1, token: c.to_string(), reason };
+
+				match c {
+					c if c == legend.initial => match initial {
+						Some((ix, iy)) => errors.push(error(format!("there's already an initial cell at ({}, {})", ix, iy))),
+						None => {
+							initial = Some((x, y));
+							map.values[i] = CellType::Initial(false);
+						}
+					}
+					c if c == legend.target => {
+						map.targets.push((x, y));
+						map.values[i] = CellType::Target;
+					}
+					c if c == legend.wall => map.values[i] = CellType::Wall(false),
+					c if c == legend.blank || c == ' ' => {}
+					'1'..='9' => map.terrain[i] = c as usize - '0' as usize,
+					_ => errors.push(error("not in the legend".to_owned()))
+				}
+			}
+		}
+
+		match initial {
+			Some(x) => map.initial = x,
+			None => errors.push(MapParseError {
+				line: end,
+				column: 1,
+				token: String::new(),
+				reason: format!("missing the initial cell, drawn as `{}`", legend.initial),
+			})
+		}
+
+		if errors.is_empty() { Ok(map) } else { Err(MapParseErrors(errors)) }
+	}
+
+	/// Draws the map as a grid of characters that [`Map::from_ascii`] reads back
+	///
+	/// Terrain only shows on open ground, so costs under the initial cell and the targets are left out,
+	/// and costs over 9 can't be drawn at all. Neither can an initial cell that's also a target.
+	pub fn to_ascii(&self, legend: &Legend) -> Result<String, Box<dyn Error>> {
+		let mut text = String::new();
+		if self.weights != CustomMoveWeight::default() {
+			writeln!(text, "weights: {}", self.weights)?;
+		}
+
+		for y in 0..self.rows {
+			for x in 0..self.cols {
+				let c = match self.read_cell((x, y)) {
+					_ if (x, y) == self.initial && self.targets.contains(&(x, y)) => {
+						return Err(format!("the initial cell ({}, {}) is also a target", x, y).into());
+					}
+					_ if (x, y) == self.initial => legend.initial,
+					CellType::Target => legend.target,
+					CellType::Wall(_) => legend.wall,
+					_ => match self.terrain((x, y)) {
+						1 => legend.blank,
+						cost @ 2..=9 => char::from(b'0' + cost as u8),
+						cost => return Err(format!("the terrain at ({}, {}) costs {}, over the 9 that can be drawn", x, y, cost).into())
+					}
+				};
+				text.push(c);
+			}
+			text.push('\n');
+		}
+
+		Ok(text)
+	}
+}
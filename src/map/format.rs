@@ -0,0 +1,68 @@
+use crate::map::{Map, CellType};
+use crate::map::ascii::Legend;
+use crate::map::parse::MapParseErrors;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::str::FromStr;
+
+/// The ways a map can be written down in a file
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MapFormat {
+	/// The size, initial cell and targets followed by rectangles of walls, as in the assignment
+	#[default]
+	Rect,
+	/// A grid of characters going by a [`Legend`], see [`Map::from_ascii`]
+	Ascii,
+}
+
+/// Error for when the format name isn't one that's known
+#[derive(Debug)]
+pub struct UnknownFormat(pub String);
+
+impl Display for UnknownFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unknown map format {}", self.0)
+	}
+}
+
+impl Error for UnknownFormat {}
+
+impl FromStr for MapFormat {
+	type Err = UnknownFormat;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_ascii_lowercase().as_ref() {
+			"rect" => MapFormat::Rect,
+			"ascii" => MapFormat::Ascii,
+			x => return Err(UnknownFormat(x.to_owned()))
+		})
+	}
+}
+
+impl Map<CellType> {
+	/// Reads a map written down in the given format, the legend only goes for the ASCII art format
+	pub fn parse_as(s: &str, format: MapFormat, legend: &Legend) -> Result<Map<CellType>, MapParseErrors> {
+		match format {
+			MapFormat::Rect => s.parse(),
+			MapFormat::Ascii => Map::from_ascii(s, legend),
+		}
+	}
+
+	/// Writes the map to a new file in the given format, refusing to overwrite one that's already there
+	pub fn save_as(&self, path: &str, format: MapFormat, legend: &Legend) -> Result<(), Box<dyn Error>> {
+		let text = match format {
+			MapFormat::Rect => self.to_rect(),
+			MapFormat::Ascii => self.to_ascii(legend)?,
+		};
+
+		let mut handle = OpenOptions::new()
+			.create_new(true)
+			.write(true)
+			.open(path)?;
+		handle.write_all(text.as_bytes())?;
+
+		Ok(())
+	}
+}
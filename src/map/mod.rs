@@ -1,6 +1,14 @@
+pub mod movement;
+pub mod parse;
+pub mod validate;
+pub mod ascii;
+pub mod format;
+pub mod movingai;
+pub mod occupancy;
+
 #[cfg(test)]
 mod tests;
-#[cfg(test)]
+#[cfg(all(test, feature = "nightly"))]
 mod benches;
 
 use rand;
@@ -9,12 +17,13 @@ use std::fmt::{self, Debug};
 use std::fmt::Display;
 #[cfg(feature = "eyecandy")]
 use ansi_term::Colour;
-use std::str::FromStr;
 use std::error::Error;
-use crate::search::Direction;
+use crate::search::{Direction, MoveWeight, CustomMoveWeight};
+use crate::utils::visited::Visited;
+use crate::map::movement::{Movement, Corners};
+use crate::map::format::MapFormat;
+use crate::map::ascii::Legend;
 use rand::random;
-use std::fs::OpenOptions;
-use std::io::Write;
 
 /// This enum denotes the possible states that any given cell on a [`Map`] can be
 #[derive(Clone, Copy, PartialEq)]
@@ -45,6 +54,15 @@ pub enum CellType {
 	/// Drawing component: turn from travelling down to left
 	#[cfg(feature = "eyecandy")]
 	PathBottomRightCorner,
+	/// Drawing component: travelling diagonally from the top left to the bottom right
+	#[cfg(feature = "eyecandy")]
+	PathFalling,
+	/// Drawing component: travelling diagonally from the bottom left to the top right
+	#[cfg(feature = "eyecandy")]
+	PathRising,
+	/// Drawing component: turning onto or off a diagonal, or landing a knight move
+	#[cfg(feature = "eyecandy")]
+	PathJoint,
 }
 
 /// Ascii-only rendering agent of the map for unit testing
@@ -64,6 +82,9 @@ impl Debug for CellType {
 				CellType::PathTopRightCorner => "+",
 				CellType::PathBottomLeftCorner => "+",
 				CellType::PathBottomRightCorner => "+",
+				CellType::PathFalling => "\\",
+				CellType::PathRising => "/",
+				CellType::PathJoint => "*",
 				_ => panic!("other variants are already covered")
 			}
 		})
@@ -89,6 +110,9 @@ impl Display for CellType {
 			CellType::PathTopRightCorner => Colour::Green.dimmed().on(Colour::Cyan).paint("┐"),
 			CellType::PathBottomLeftCorner => Colour::Green.dimmed().on(Colour::Cyan).paint("└"),
 			CellType::PathBottomRightCorner => Colour::Green.dimmed().on(Colour::Cyan).paint("┘"),
+			CellType::PathFalling => Colour::Green.dimmed().on(Colour::Cyan).paint("╲"),
+			CellType::PathRising => Colour::Green.dimmed().on(Colour::Cyan).paint("╱"),
+			CellType::PathJoint => Colour::Green.dimmed().on(Colour::Cyan).paint("•"),
 		})
 	}
 }
@@ -103,8 +127,14 @@ pub struct Map<Tag> {
 	pub initial: (usize, usize),
 	/// Target states
 	pub targets: Vec<(usize, usize)>,
+	/// The moves that can be made between cells, four-way unless set otherwise
+	pub movement: Movement,
+	/// The weights of the moves when they're varied, the assignment's unless set otherwise
+	pub weights: CustomMoveWeight,
 	/// Data structure that holds the grid
 	values: Vec<Tag>,
+	/// How many times over a move costs when it lands on each cell, 1 for plain ground
+	terrain: Vec<usize>,
 }
 
 impl<Tag: Clone> Clone for Map<Tag> {
@@ -114,90 +144,14 @@ impl<Tag: Clone> Clone for Map<Tag> {
 			cols: self.cols,
 			initial: self.initial,
 			targets: self.targets.clone(),
+			movement: self.movement,
+			weights: self.weights,
 			values: self.values.clone(),
+			terrain: self.terrain.clone(),
 		}
 	}
 }
 
-/// Maze parser component: turn an array (string) into an array (`Vec<number>`)
-fn num_array(source: &str) -> Vec<usize> {
-	source
-		.trim()
-		.trim_start_matches("[")
-		.trim_end_matches("]")
-		.trim()
-		.trim_start_matches("(")
-		.trim_end_matches(")")
-		.trim()
-		.split(",")
-		.map(|x| x.trim().parse::<usize>().expect("not an unsigned number"))
-		.collect()
-}
-
-/// Upper-level implementation of the maze parser
-impl FromStr for Map<CellType> {
-	type Err = Box<dyn Error>;
-
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut lines = s.lines();
-		let (rows, cols) = {
-			let grid_size = num_array(lines.next()
-				.expect("missing grid size definition"));
-			(grid_size[0], grid_size[1])
-		};
-
-		let mut map = Map {
-			rows,
-			cols,
-			initial: (0, 0), // Default value, not overwritten soon
-			targets: vec![],
-			values: vec![CellType::Blank(false); rows * cols],
-		};
-
-		// initial cell pos
-		{
-			let initial = num_array(lines.next().expect("starting position required"));
-			let i = map.index((initial[0], initial[1]));
-			map.values[i] = CellType::Initial(false);
-
-			map.initial = (initial[0], initial[1]);
-		}
-
-		// valid destinations pos
-		{
-			for target in lines.next().expect("destination position required").split("|") {
-				let target = num_array(target);
-				let i = map.index((target[0], target[1]));
-				map.values[i] = CellType::Target;
-				map.targets.push((target[0], target[1]));
-			}
-		}
-
-		// walls
-		{
-			while let Some(wall) = lines.next() {
-				let wall = num_array(wall);
-				// initial coords
-				let ix = wall[0];
-				let iy = wall[1];
-
-				// "delta" coords (the dimensions)
-				let dx = wall[2];
-				let dy = wall[3];
-
-				for x in ix..dx + ix {
-					for y in iy..dy + iy {
-						let i = map.index((x, y));
-						map.values[i] = CellType::Wall(false);
-					}
-				}
-			}
-		}
-
-		Ok(map)
-	}
-}
-
 impl<Tag> Map<Tag> {
 	pub fn index(&self, coords: (usize, usize)) -> usize {
 		coords.0 + coords.1 * self.cols
@@ -237,41 +191,55 @@ impl<Tag> Map<Tag> {
 		&mut self.values[i]
 	}
 
-	/// Finds valid directions and return the associated cursor
-	pub fn adjacents(&self, cur: (usize, usize)) -> Vec<(Direction, (usize, usize))> {
-		Direction::iter()
-			.map(|d| (*d, self.adjacent(cur, *d)))
-			.filter(|x| x.1.is_some())
-			.map(|x| (x.0, x.1.expect("memory corruption")))
-			.collect()
+	/// How many times over a move costs when it lands on the cell
+	pub fn terrain(&self, cur: (usize, usize)) -> usize {
+		self.terrain[self.index(cur)]
+	}
+
+	/// Makes moves onto the cell cost `cost` times over, which has to be at least 1 so the heuristics don't overestimate
+	pub fn set_terrain(&mut self, cur: (usize, usize), cost: usize) {
+		assert!(cost >= 1, "terrain costs start at 1");
+		let i = self.index(cur);
+		self.terrain[i] = cost;
+	}
+
+	/// Whether any cell costs more than plain ground
+	pub fn has_terrain(&self) -> bool {
+		self.terrain.iter().any(|x| *x != 1)
+	}
+
+	/// Weight of moving in `direction` onto the cell `onto`, the weight of the move times the terrain there
+	pub fn move_cost<W: MoveWeight>(&self, weigher: &W, direction: Direction, onto: (usize, usize)) -> usize {
+		weigher.weigh(&direction) * self.terrain(onto)
+	}
+
+	/// Total weight of following the path from the initial cell
+	pub fn path_cost<W: MoveWeight>(&self, weigher: &W, path: &[Direction]) -> usize {
+		let mut cursor = self.initial;
+		path.iter()
+			.map(|d| {
+				cursor = self.adjacent(cursor, *d).expect("path given is not valid");
+				self.move_cost(weigher, *d, cursor)
+			})
+			.sum()
+	}
+
+	/// Follows the path from the `from` cell, and returns the coordinates of the cell it ends on
+	pub fn walk(&self, from: (usize, usize), path: &[Direction]) -> (usize, usize) {
+		path.iter()
+			.fold(from, |cur, d| self.adjacent(cur, *d).expect("path given is not valid"))
 	}
 
 	/// Returns the coordinates of the adjacent cell, if none, return a [`None`]
+	///
+	/// Where the move lands depends on the [`Movement`], but walls are left for the caller to deal with
 	pub fn adjacent(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
-		match direction {
-			Direction::Up => {
-				if y > 0 {
-					return Some((x, y - 1));
-				}
-			}
-			Direction::Left => {
-				if x > 0 {
-					return Some((x - 1, y));
-				}
-			}
-			Direction::Down => {
-				if y < self.rows - 1 {
-					return Some((x, y + 1));
-				}
-			}
-			Direction::Right => {
-				if x < self.cols - 1 {
-					return Some((x + 1, y));
-				}
-			}
-		}
+		let (dx, dy) = self.movement.delta(direction, y);
+
+		let x = x.checked_add_signed(dx).filter(|x| *x < self.cols)?;
+		let y = y.checked_add_signed(dy).filter(|y| *y < self.rows)?;
 
-		return None;
+		return Some((x, y));
 	}
 }
 
@@ -281,15 +249,20 @@ macro_rules! even {
 }
 
 impl Map<CellType> {
-	/// Serializes the current map and writes to a file
+	/// Serializes the current map in the rectangle format and writes to a file, see [`Map::save_as`] for the others
 	pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
-		let mut handle = OpenOptions::new()
-			.create_new(true)
-			.write(true)
-			.open(path)?;
+		self.save_as(path, MapFormat::Rect, &Legend::default())
+	}
 
-		writeln!(handle, "[{}, {}]", self.rows, self.cols)?;
-		writeln!(handle, "({}, {})", self.initial.0, self.initial.1)?;
+	/// Writes the map out in the rectangle format, with every wall and patch of terrain as a rectangle of its own
+	pub fn to_rect(&self) -> String {
+		let mut text = String::new();
+
+		if self.weights != CustomMoveWeight::default() {
+			text += &format!("weights: {}\n", self.weights);
+		}
+		text += &format!("[{}, {}]\n", self.rows, self.cols);
+		text += &format!("({}, {})\n", self.initial.0, self.initial.1);
 
 		let targets: Vec<String> = self.targets.iter()
 			.map(|(x, y)| {
@@ -297,16 +270,85 @@ impl Map<CellType> {
 			})
 			.collect();
 
-		writeln!(handle, "{}", targets.join(" | "))?;
+		text += &format!("{}\n", targets.join(" | "));
 
-		let mut cells = self.values.iter().enumerate();
-		while let Some((idx, val)) = cells.next() {
+		for (idx, val) in self.values.iter().enumerate() {
 			if let CellType::Wall(_) = val {
-				writeln!(handle, "({}, {}, 1, 1)", idx % self.cols, idx / self.cols)?;
+				text += &format!("({}, {}, 1, 1)\n", idx % self.cols, idx / self.cols);
 			}
 		}
 
-		Ok(())
+		for (idx, cost) in self.terrain.iter().enumerate() {
+			if *cost != 1 {
+				text += &format!("({}, {}, 1, 1, {})\n", idx % self.cols, idx / self.cols, cost);
+			}
+		}
+
+		return text;
+	}
+
+	/// Copy of the map that starts on `from` and has `to` as its only target, the other targets are blanked out
+	///
+	/// `from` and `to` can be the same cell, in which case the target wins
+	pub fn between(&self, from: (usize, usize), to: (usize, usize)) -> Map<CellType> {
+		let mut map = self.clone();
+
+		*map.read_cell_mut(map.initial) = CellType::Blank(false);
+		for target in &self.targets {
+			*map.read_cell_mut(*target) = CellType::Blank(false);
+		}
+
+		map.initial = from;
+		map.targets = vec![to];
+		*map.read_cell_mut(from) = CellType::Initial(false);
+		*map.read_cell_mut(to) = CellType::Target;
+
+		return map;
+	}
+
+	/// Finds valid directions and return the associated cursor
+	///
+	/// The moves are the ones allowed by the [`Movement`] of the map, leaving out the diagonals that clip walls they shouldn't
+	pub fn adjacents(&self, cur: (usize, usize)) -> Vec<(Direction, (usize, usize))> {
+		self.movement.directions().iter()
+			.filter_map(|d| Some((*d, self.adjacent(cur, *d)?)))
+			.filter(|(d, _)| self.clears_corners(cur, *d))
+			.collect()
+	}
+
+	/// The moves that land on the cell, along with the cells they're made from, the way [`Map::adjacents`] goes the other way
+	pub fn predecessors(&self, cur: (usize, usize)) -> Vec<(Direction, (usize, usize))> {
+		self.movement.directions().iter()
+			.filter_map(|d| {
+				let prev = self.adjacent(cur, d.opposite())?;
+				// Every move undoes its opposite, but the corners are only checked the way the move goes
+				if self.adjacent(prev, *d) != Some(cur) || !self.clears_corners(prev, *d) {
+					return None;
+				}
+				Some((*d, prev))
+			})
+			.collect()
+	}
+
+	/// Whether the move gets past the walls beside it, going by the [`Corners`] rule of eight-way movement
+	pub fn clears_corners(&self, (x, y): (usize, usize), direction: Direction) -> bool {
+		let corners = match self.movement {
+			Movement::EightWay(corners) if direction.is_diagonal() => corners,
+			_ => return true
+		};
+
+		// The cells beside a diagonal move are the ones it would pass through going along each axis in turn
+		let (dx, dy) = direction.delta();
+		let walls = [(x.wrapping_add_signed(dx), y), (x, y.wrapping_add_signed(dy))].iter()
+			.filter(|(x, y)| *x < self.cols && *y < self.rows)
+			.filter(|cell| matches!(self.read_cell(**cell), CellType::Wall(_)))
+			.count();
+
+		match corners {
+			Corners::Cut => true,
+			Corners::NoSqueeze => walls < 2,
+			Corners::Avoid => walls == 0,
+		}
 	}
 
 	/// Clears all the visit markers in the [`CellType`] enum
@@ -318,6 +360,15 @@ impl Map<CellType> {
 		});
 	}
 
+	/// Writes the visit markers in the [`CellType`] enum from a search's closed set, for visualisation
+	pub fn mark_visits(&mut self, visited: &Visited) {
+		for coords in visited.iter() {
+			if let CellType::Blank(ref mut b) | CellType::Initial(ref mut b) | CellType::Wall(ref mut b) = self.read_cell_mut(coords) {
+				*b = true;
+			}
+		}
+	}
+
 	/// Visualize the list of directions taken by the cursor by projecting onto the map
 	/// fancy paths enums in [`CellType`]
 	#[cfg(feature = "eyecandy")]
@@ -328,6 +379,10 @@ impl Map<CellType> {
 		for this in path {
 			if let Some(ref prev) = prev {
 				*self.read_cell_mut(cursor) = match (prev, this) {
+					(Direction::UpLeft, Direction::UpLeft) | (Direction::DownRight, Direction::DownRight) => CellType::PathFalling,
+					(Direction::DownLeft, Direction::DownLeft) | (Direction::UpRight, Direction::UpRight) => CellType::PathRising,
+					// There's no box drawing character for the other turns off diagonals, nor for knight moves
+					(prev, this) if prev.is_diagonal() || this.is_diagonal() => CellType::PathJoint,
 					(Direction::Right, Direction::Up) => CellType::PathBottomRightCorner,
 					(Direction::Right, Direction::Down) => CellType::PathTopRightCorner,
 					(Direction::Right, _) => CellType::PathHor,
@@ -340,6 +395,7 @@ impl Map<CellType> {
 					(Direction::Down, Direction::Left) => CellType::PathBottomRightCorner,
 					(Direction::Down, Direction::Right) => CellType::PathBottomLeftCorner,
 					(Direction::Down, _) => CellType::PathVer,
+					_ => unreachable!("only straight moves are left")
 				};
 			}
 
@@ -422,15 +478,24 @@ impl Map<CellType> {
 			cols,
 			initial: (random::<usize>() % cols, random::<usize>() % rows),
 			targets: vec![],
+			movement: Movement::default(),
+			weights: CustomMoveWeight::default(),
 			values: vec![CellType::Blank(false); rows * cols],
+			terrain: vec![1; rows * cols],
 		};
 
 		map.random_maze_subdivision((0, 0), rows, cols);
 
 		*map.read_cell_mut(map.initial) = CellType::Initial(false);
 
-		for _ in 0..targets {
-			let coords = (random::<usize>() % cols, random::<usize>() % rows);
+		// Each target on a cell of its own, as far as there are cells to go around
+		for _ in 0..targets.min((rows * cols).saturating_sub(1)) {
+			let coords = loop {
+				let coords = (random::<usize>() % cols, random::<usize>() % rows);
+				if coords != map.initial && !map.targets.contains(&coords) {
+					break coords;
+				}
+			};
 
 			map.targets.push(coords);
 			*map.read_cell_mut(coords) = CellType::Target;
@@ -438,6 +503,26 @@ impl Map<CellType> {
 
 		return map;
 	}
+
+	/// Scatters `patches` rectangles of costlier terrain over the map, each costing from 2 up to `max_cost` times over
+	///
+	/// The patches can be up to a quarter of the map across, and land on whatever's there, walls and targets included
+	pub fn scatter_terrain(&mut self, patches: usize, max_cost: usize) {
+		assert!(max_cost >= 2, "terrain has to cost more than plain ground");
+
+		for _ in 0..patches {
+			let rows = random::<usize>() % (self.rows / 4 + 1) + 1;
+			let cols = random::<usize>() % (self.cols / 4 + 1) + 1;
+			let (ix, iy) = (random::<usize>() % (self.cols - cols + 1), random::<usize>() % (self.rows - rows + 1));
+			let cost = random::<usize>() % (max_cost - 1) + 2;
+
+			for x in ix..cols + ix {
+				for y in iy..rows + iy {
+					self.set_terrain((x, y), cost);
+				}
+			}
+		}
+	}
 }
 
 /// ASCII-only renderer of a Map for unit testing
@@ -0,0 +1,105 @@
+use crate::search::Direction;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// The moves the agent can make from one cell to the next, which decides what [`Map::adjacents`](crate::map::Map::adjacents) hands out
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Movement {
+	/// Up, left, down and right, as in the assignment
+	#[default]
+	FourWay,
+	/// The diagonals as well, with a rule for getting past the corners of walls
+	EightWay(Corners),
+	/// The L-shaped moves of a chess knight, which jump over whatever's in between
+	Knight,
+	/// Hexagonal cells, with every odd row pushed half a cell to the right
+	///
+	/// The six neighbours are left and right, then `Up-Left`, `Up-Right`, `Down-Left` and `Down-Right`,
+	/// which land on the two cells touching it in the rows above and below.
+	Hex,
+}
+
+/// What a diagonal move does about walls on the two cells it passes between
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Corners {
+	/// Walls beside the move don't matter, only the cell that's moved onto
+	Cut,
+	/// One wall beside the move is fine, but it can't squeeze between two
+	NoSqueeze,
+	/// Both cells beside the move have to be open, so paths never clip a wall
+	#[default]
+	Avoid,
+}
+
+impl Movement {
+	/// The moves that can be made, in the order they're tried
+	pub fn directions(&self) -> &'static [Direction] {
+		match self {
+			Movement::FourWay => &[Direction::Up, Direction::Left, Direction::Down, Direction::Right],
+			Movement::EightWay(_) => &[
+				Direction::Up, Direction::Left, Direction::Down, Direction::Right,
+				Direction::UpLeft, Direction::DownLeft, Direction::DownRight, Direction::UpRight,
+			],
+			Movement::Knight => &[
+				Direction::UpUpLeft, Direction::LeftLeftUp, Direction::LeftLeftDown, Direction::DownDownLeft,
+				Direction::DownDownRight, Direction::RightRightDown, Direction::RightRightUp, Direction::UpUpRight,
+			],
+			Movement::Hex => &[
+				Direction::UpLeft, Direction::Left, Direction::DownLeft,
+				Direction::DownRight, Direction::Right, Direction::UpRight,
+			],
+		}
+	}
+
+	/// Where the move lands relative to the cell it's made from, which only depends on the row for [`Movement::Hex`]
+	pub fn delta(&self, direction: Direction, row: usize) -> (isize, isize) {
+		let (dx, dy) = direction.delta();
+		match self {
+			// Odd rows are pushed right, so going up or down from one lands on the same column or the one to the right
+			Movement::Hex if dy != 0 => {
+				if row % 2 == 1 { ((dx + 1) / 2, dy) } else { ((dx - 1) / 2, dy) }
+			}
+			_ => (dx, dy)
+		}
+	}
+}
+
+/// Error for when a movement model or corner rule is not recognised
+#[derive(Debug)]
+pub struct UnknownMovement(pub String);
+
+impl Display for UnknownMovement {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unrecognized movement: {}", self.0)
+	}
+}
+
+impl Error for UnknownMovement {}
+
+impl FromStr for Movement {
+	type Err = UnknownMovement;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_ascii_lowercase().as_ref() {
+			"4" | "four" => Movement::FourWay,
+			"8" | "eight" => Movement::EightWay(Corners::default()),
+			"knight" => Movement::Knight,
+			"hex" => Movement::Hex,
+			x => return Err(UnknownMovement(x.to_owned()))
+		})
+	}
+}
+
+impl FromStr for Corners {
+	type Err = UnknownMovement;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_ascii_lowercase().as_ref() {
+			"cut" => Corners::Cut,
+			"no-squeeze" => Corners::NoSqueeze,
+			"avoid" => Corners::Avoid,
+			x => return Err(UnknownMovement(x.to_owned()))
+		})
+	}
+}
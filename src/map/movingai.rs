@@ -0,0 +1,114 @@
+use crate::map::{Map, CellType};
+use crate::map::movement::{Movement, Corners};
+use crate::map::parse::{MapParseError, MapParseErrors};
+use crate::search::CustomMoveWeight;
+
+impl Map<CellType> {
+	/// Reads a map of the Moving AI Lab benchmarks, the `.map` files of <https://movingai.com/benchmarks/grids.html>
+	///
+	/// `.`, `G` and `S` (swamp) are open ground, while `@`, `O`, `T` (trees) and `W` (water) are walls.
+	/// The maps come without an initial cell or targets, those are in the scenarios that go with them,
+	/// so the initial cell is left on the first open cell and there are no targets, see [`Map::between`].
+	/// The benchmarks move eight ways without cutting corners, which is what the movement of the map is set to.
+	pub fn from_movingai(s: &str) -> Result<Map<CellType>, MapParseErrors> {
+		let mut lines = s.lines().enumerate().map(|(i, x)| (i + 1, x));
+		let mut errors = vec![];
+		let error = |line: usize, column: usize, token: &str, reason: &str| MapParseError {
+			line,
+			column,
+			token: token.to_owned(),
+			reason: reason.to_owned(),
+		};
+
+		// The header is `type octile`, `height <rows>` and `width <cols>` in any order, up until the `map` line
+		let (mut rows, mut cols) = (None, None);
+		let mut end = 1;
+		loop {
+			let (number, text) = match lines.next() {
+				Some(x) => x,
+				None => {
+					errors.push(error(end, 1, "", "missing the `map` line"));
+					return Err(MapParseErrors(errors));
+				}
+			};
+			end = number + 1;
+			let mut words = text.split_whitespace();
+
+			match (words.next(), words.next(), words.next()) {
+				(Some("map"), None, _) => break,
+				(Some("type"), Some("octile"), None) => {}
+				(Some("type"), Some(x), None) => errors.push(error(number, text.find(x).expect("split from it") + 1, x, "only octile maps are supported")),
+				(Some(key @ ("height" | "width")), Some(x), None) => match x.parse::<usize>() {
+					Ok(0) | Err(_) => errors.push(error(number, text.find(x).expect("split from it") + 1, x, "not a positive number")),
+					Ok(n) if key == "height" => rows = Some(n),
+					Ok(n) => cols = Some(n),
+				}
+				(None, _, _) => {}
+				_ => errors.push(error(number, 1, text.trim(), "expected `type`, `height`, `width` or `map`"))
+			}
+		}
+
+		let (rows, cols) = match (rows, cols) {
+			(Some(rows), Some(cols)) => (rows, cols),
+			(rows, _) => {
+				let missing = if rows.is_none() { "missing the height" } else { "missing the width" };
+				errors.push(error(end - 1, 1, "", missing));
+				return Err(MapParseErrors(errors));
+			}
+		};
+
+		// The cells are put together as the rows are read, rather than sized up front from a header that might be way off
+		let mut values = vec![];
+		for y in 0..rows {
+			let (number, text) = match lines.next() {
+				Some(x) => x,
+				None => {
+					errors.push(error(end, 1, "", &format!("missing {} of the {} rows", rows - y, rows)));
+					break;
+				}
+			};
+			end = number + 1;
+
+			let text = text.trim_end();
+			if text.chars().count() != cols {
+				errors.push(error(number, 1, "", &format!("the row is {} cells wide rather than {}", text.chars().count(), cols)));
+			}
+
+			for (x, c) in text.chars().take(cols).enumerate() {
+				values.push(match c {
+					'.' | 'G' | 'S' => CellType::Blank(false),
+					'@' | 'O' | 'T' | 'W' => CellType::Wall(false),
+					c => {
+						errors.push(error(number, x + 1, &c.to_string(), "not a terrain of the benchmarks"));
+						CellType::Blank(false)
+					}
+				});
+			}
+		}
+
+		if let Some((number, text)) = lines.find(|x| !x.1.trim().is_empty()) {
+			errors.push(error(number, 1, text.trim(), "more rows than the height"));
+		}
+		if !errors.is_empty() {
+			return Err(MapParseErrors(errors));
+		}
+
+		// Every row is there and as wide as it should be, so there's a cell for each of the rows and columns
+		let open = match values.iter().position(|x| matches!(x, CellType::Blank(_))) {
+			Some(i) => i,
+			None => return Err(MapParseErrors(vec![error(end, 1, "", "there's no open ground")]))
+		};
+		values[open] = CellType::Initial(false);
+
+		Ok(Map {
+			rows,
+			cols,
+			initial: (open % cols, open / cols),
+			targets: vec![],
+			movement: Movement::EightWay(Corners::Avoid),
+			weights: CustomMoveWeight::default(),
+			values,
+			terrain: vec![1; rows * cols],
+		})
+	}
+}
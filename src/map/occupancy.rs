@@ -0,0 +1,281 @@
+use crate::map::{Map, CellType};
+use crate::map::movement::Movement;
+use crate::map::parse::{MapParseError, MapParseErrors};
+use crate::search::CustomMoveWeight;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// How the shades of an occupancy grid are told apart, the way ROS `map_server` does it
+///
+/// Each pixel is turned into the odds of the cell being occupied, 1 for black and 0 for white, or the other way around with `negate`.
+/// Odds over `occupied` are walls, odds under `free` are open ground, and anything in between is `unknown`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Occupancy {
+	pub occupied: f64,
+	pub free: f64,
+	pub negate: bool,
+	/// What the cells that are neither occupied nor free are taken as
+	pub unknown: CellType,
+}
+
+/// The thresholds of `map_server`, and unknown cells kept out of
+impl Default for Occupancy {
+	fn default() -> Self {
+		Occupancy {
+			occupied: 0.65,
+			free: 0.196,
+			negate: false,
+			unknown: CellType::Wall(false),
+		}
+	}
+}
+
+impl Occupancy {
+	/// Sorts a pixel, with its brightness from 0 to 1
+	fn classify(&self, brightness: f64) -> CellType {
+		let odds = if self.negate { brightness } else { 1.0 - brightness };
+
+		if odds > self.occupied {
+			CellType::Wall(false)
+		} else if odds < self.free {
+			CellType::Blank(false)
+		} else {
+			self.unknown
+		}
+	}
+}
+
+/// Error for when an image can't be made sense of
+#[derive(Debug)]
+pub struct InvalidImage(pub String);
+
+impl Display for InvalidImage {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid image: {}", self.0)
+	}
+}
+
+impl Error for InvalidImage {}
+
+/// Reads the whitespace separated numbers of a PGM or PPM, skipping the `#` comments
+struct Tokens<'a> {
+	bytes: &'a [u8],
+	at: usize,
+}
+
+impl<'a> Tokens<'a> {
+	fn skip_space(&mut self) {
+		while let Some(c) = self.bytes.get(self.at) {
+			match c {
+				b'#' => while self.bytes.get(self.at).is_some_and(|x| *x != b'\n') {
+					self.at += 1;
+				},
+				c if c.is_ascii_whitespace() => self.at += 1,
+				_ => return
+			}
+		}
+	}
+
+	fn number(&mut self, what: &str) -> Result<usize, InvalidImage> {
+		self.skip_space();
+		let start = self.at;
+		while self.bytes.get(self.at).is_some_and(|x| x.is_ascii_digit()) {
+			self.at += 1;
+		}
+
+		std::str::from_utf8(&self.bytes[start..self.at]).ok()
+			.and_then(|x| x.parse::<usize>().ok())
+			.ok_or_else(|| InvalidImage(format!("expected {} at byte {}", what, start)))
+	}
+}
+
+impl Map<CellType> {
+	/// Reads a PGM or PPM image as an occupancy grid, in either the plain (`P2`, `P3`) or the binary (`P5`, `P6`) variant
+	///
+	/// Every pixel is a cell, with the top row of the image as the top row of the map. The colours of a PPM are averaged.
+	/// Like the benchmark maps, the image comes without an initial cell or targets,
+	/// so the initial cell is left on the first open cell and there are no targets, see [`Map::between`].
+	pub fn from_pnm(bytes: &[u8], occupancy: &Occupancy) -> Result<Map<CellType>, InvalidImage> {
+		let (channels, binary) = match bytes.get(..2) {
+			Some(b"P2") => (1, false),
+			Some(b"P3") => (3, false),
+			Some(b"P5") => (1, true),
+			Some(b"P6") => (3, true),
+			_ => return Err(InvalidImage("only PGM and PPM images are supported, starting with P2, P3, P5 or P6".to_owned()))
+		};
+
+		let mut tokens = Tokens { bytes, at: 2 };
+		let cols = tokens.number("the width")?;
+		let rows = tokens.number("the height")?;
+		let max = tokens.number("the maximum value")?;
+		if rows == 0 || cols == 0 {
+			return Err(InvalidImage("the image has no pixels".to_owned()));
+		}
+		if !(1..=65535).contains(&max) {
+			return Err(InvalidImage(format!("the maximum value has to be from 1 to 65535, not {}", max)));
+		}
+
+		let too_big = || InvalidImage(format!("a {} by {} image is too big", cols, rows));
+		let samples = rows.checked_mul(cols).and_then(|x| x.checked_mul(channels)).ok_or_else(too_big)?;
+		let values: Vec<usize> = if binary {
+			// Exactly one whitespace character between the header and the pixels
+			let start = tokens.at + 1;
+			let width = if max > 255 { 2 } else { 1 };
+			let length = samples.checked_mul(width).ok_or_else(too_big)?;
+			let raster = start.checked_add(length).and_then(|end| bytes.get(start..end))
+				.ok_or_else(|| InvalidImage(format!("expected {} bytes of pixels, found {}", length, bytes.len().saturating_sub(start))))?;
+
+			raster.chunks(width)
+				.map(|x| x.iter().fold(0, |acc, b| acc << 8 | *b as usize))
+				.collect()
+		} else {
+			(0..samples).map(|_| tokens.number("a pixel")).collect::<Result<_, _>>()?
+		};
+		if let Some(x) = values.iter().find(|x| **x > max) {
+			return Err(InvalidImage(format!("the pixel value {} is over the maximum of {}", x, max)));
+		}
+
+		let cells: Vec<CellType> = values.chunks(channels)
+			.map(|x| occupancy.classify(x.iter().sum::<usize>() as f64 / (channels * max) as f64))
+			.collect();
+		let open = cells.iter().position(|x| matches!(x, CellType::Blank(_)))
+			.ok_or_else(|| InvalidImage("there's no open ground".to_owned()))?;
+
+		let mut map = Map {
+			rows,
+			cols,
+			initial: (open % cols, open / cols),
+			targets: vec![],
+			movement: Movement::default(),
+			weights: CustomMoveWeight::default(),
+			values: cells,
+			terrain: vec![1; rows * cols],
+		};
+		map.values[open] = CellType::Initial(false);
+
+		Ok(map)
+	}
+}
+
+/// The YAML that goes with an occupancy grid for ROS `map_server`, for finding the image and getting between cells and world metres
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapServer {
+	/// Where the image is, relative to the YAML file unless it's an absolute path
+	pub image: String,
+	/// Metres across each cell
+	pub resolution: f64,
+	/// World metres of the bottom left corner of the image, and its yaw, which is ignored like `map_server` does
+	pub origin: (f64, f64, f64),
+	/// The thresholds and `negate`, with unknown cells as walls to begin with
+	pub occupancy: Occupancy,
+}
+
+impl MapServer {
+	/// Cell that the point in world metres falls in, [`None`] if it's off the map
+	pub fn cell(&self, map: &Map<CellType>, (x, y): (f64, f64)) -> Option<(usize, usize)> {
+		let col = ((x - self.origin.0) / self.resolution).floor();
+		let row = ((y - self.origin.1) / self.resolution).floor();
+		if !(0.0..map.cols as f64).contains(&col) || !(0.0..map.rows as f64).contains(&row) {
+			return None;
+		}
+
+		// The world goes up from the bottom of the image, while the rows go down from the top
+		Some((col as usize, map.rows - 1 - row as usize))
+	}
+
+	/// World metres of the middle of the cell
+	pub fn world(&self, map: &Map<CellType>, (x, y): (usize, usize)) -> (f64, f64) {
+		(
+			self.origin.0 + (x as f64 + 0.5) * self.resolution,
+			self.origin.1 + ((map.rows - 1 - y) as f64 + 0.5) * self.resolution,
+		)
+	}
+}
+
+/// Reads the flat `key: value` lines of the YAML, comments and keys that don't matter here are skipped
+///
+/// `image`, `resolution` and `origin` are needed, `negate`, `occupied_thresh` and `free_thresh` default to those of [`Occupancy`],
+/// and `mode` has to be `trinary` or `scale` if it's there, either of which sort the cells the same way
+impl FromStr for MapServer {
+	type Err = MapParseErrors;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut errors = vec![];
+		let (mut image, mut resolution, mut origin) = (None, None, None);
+		let mut occupancy = Occupancy::default();
+
+		for (i, text) in s.lines().enumerate() {
+			let text = text.split('#').next().expect("split always gives one");
+			let (key, value) = match text.split_once(':') {
+				Some(x) => x,
+				None if text.trim().is_empty() => continue,
+				None => {
+					errors.push(MapParseError { line: i + 1, column: 1, token: text.trim().to_owned(), reason: "expected key: value".to_owned() });
+					continue;
+				}
+			};
+
+			let at = key.len() + 1 + (value.len() - value.trim_start().len());
+			let value = value.trim();
+			let error = |reason: &str| MapParseError {
+				line: i + 1,
+				column: text[..at].chars().count() + 1,
+				token: value.to_owned(),
+				reason: reason.to_owned(),
+			};
+			let number = || value.parse::<f64>().ok().filter(|x| x.is_finite());
+			let odds = || number().filter(|x| (0.0..=1.0).contains(x)).ok_or_else(|| error("a threshold has to be from 0 to 1"));
+
+			match key.trim() {
+				"image" => image = Some(value.trim_matches(|c| c == '"' || c == '\'').to_owned()),
+				"resolution" => match number().filter(|x| *x > 0.0) {
+					Some(x) => resolution = Some(x),
+					None => errors.push(error("the resolution has to be a positive number"))
+				}
+				"origin" => {
+					let numbers: Vec<Option<f64>> = value.trim_start_matches('[').trim_end_matches(']').split(',')
+						.map(|x| x.trim().parse::<f64>().ok())
+						.collect();
+					match numbers[..] {
+						[Some(x), Some(y), Some(yaw)] => origin = Some((x, y, yaw)),
+						_ => errors.push(error("the origin takes 3 numbers, as in [x, y, yaw]"))
+					}
+				}
+				"negate" => match value {
+					"0" | "false" => occupancy.negate = false,
+					"1" | "true" => occupancy.negate = true,
+					_ => errors.push(error("negate has to be 0 or 1"))
+				}
+				"occupied_thresh" => match odds() {
+					Ok(x) => occupancy.occupied = x,
+					Err(e) => errors.push(e)
+				}
+				"free_thresh" => match odds() {
+					Ok(x) => occupancy.free = x,
+					Err(e) => errors.push(e)
+				}
+				"mode" if value != "trinary" && value != "scale" => errors.push(error("only the trinary and scale modes are supported")),
+				_ => {}
+			}
+		}
+
+		let end = s.lines().count() + 1;
+		let missing = |what: &str| MapParseError { line: end, column: 1, token: String::new(), reason: format!("missing the {}", what) };
+		match (image, resolution, origin) {
+			(Some(image), Some(resolution), Some(origin)) if errors.is_empty() => Ok(MapServer { image, resolution, origin, occupancy }),
+			(image, resolution, origin) => {
+				if image.is_none() {
+					errors.push(missing("image"));
+				}
+				if resolution.is_none() && !errors.iter().any(|x| x.reason.starts_with("the resolution")) {
+					errors.push(missing("resolution"));
+				}
+				if origin.is_none() && !errors.iter().any(|x| x.reason.starts_with("the origin")) {
+					errors.push(missing("origin"));
+				}
+				Err(MapParseErrors(errors))
+			}
+		}
+	}
+}
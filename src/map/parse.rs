@@ -0,0 +1,289 @@
+use crate::map::{Map, CellType};
+use crate::map::movement::Movement;
+use crate::search::CustomMoveWeight;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// The most cells a map file can ask for, as many as in 4096 by 4096, which is well past the benchmark maps
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// Something wrong with a map file, and where it is
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapParseError {
+	/// Line of the file, counting from 1
+	pub line: usize,
+	/// Character along the line, counting from 1
+	pub column: usize,
+	/// The text that's wrong, empty when something's missing
+	pub token: String,
+	pub reason: String,
+}
+
+/// Written as `line:column: reason`, the way compilers point at things
+impl Display for MapParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}:{}: {}", self.line, self.column, self.reason)?;
+		if !self.token.is_empty() {
+			write!(f, ", found `{}`", self.token)?;
+		}
+		Ok(())
+	}
+}
+
+impl Error for MapParseError {}
+
+/// Everything wrong with a map file, in the order it comes up in the file
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MapParseErrors(pub Vec<MapParseError>);
+
+/// One error per line
+impl Display for MapParseErrors {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let lines: Vec<String> = self.0.iter().map(|x| x.to_string()).collect();
+		write!(f, "{}", lines.join("\n"))
+	}
+}
+
+impl Error for MapParseErrors {}
+
+/// A line of the file, for pointing errors at the right place on it
+struct Line<'a> {
+	number: usize,
+	text: &'a str,
+}
+
+impl<'a> Line<'a> {
+	/// An error at the byte offset `at` along the line
+	fn error(&self, at: usize, token: &str, reason: impl Into<String>) -> MapParseError {
+		MapParseError {
+			line: self.number,
+			column: self.text[..at].chars().count() + 1,
+			token: token.to_owned(),
+			reason: reason.into(),
+		}
+	}
+
+	/// Reads a tuple of numbers such as `[5, 11]` or `(2, 0, 2, 2)` out of the bytes from `start` to `end`,
+	/// returning each number along with the byte offset it's at
+	///
+	/// The brackets can be left out, but if there's an opening one there has to be a closing one with nothing after it
+	fn tuple(&self, start: usize, end: usize) -> Result<Vec<(usize, usize)>, Vec<MapParseError>> {
+		let text = &self.text[start..end];
+		let lead = text.len() - text.trim_start().len();
+		let mut inside = (start + lead, start + text.trim_end().len());
+
+		// Text after the closing bracket is reported after whatever is wrong with the numbers before it
+		let mut trailing = None;
+		let opening = self.text[inside.0..inside.1].chars().next();
+		if let Some(open @ ('[' | '(')) = opening {
+			let close = if open == '[' { ']' } else { ')' };
+			let rest = &self.text[inside.0..inside.1];
+
+			match rest.find(close) {
+				Some(i) => {
+					let after = &rest[i + 1..];
+					if !after.trim().is_empty() {
+						let at = inside.0 + i + 1 + (after.len() - after.trim_start().len());
+						trailing = Some(self.error(at, after.trim(), "unexpected text after the closing bracket"));
+					}
+					inside = (inside.0 + 1, inside.0 + i);
+				}
+				None => return Err(vec![self.error(inside.0, rest, format!("the `{}` is never closed", open))])
+			}
+		}
+
+		let mut numbers = vec![];
+		let mut errors = vec![];
+		let mut at = inside.0;
+		for item in self.text[inside.0..inside.1].split(',') {
+			let token = item.trim();
+			let column = at + (item.len() - item.trim_start().len());
+			at += item.len() + 1;
+
+			match token.parse::<usize>() {
+				Ok(x) => numbers.push((x, column)),
+				Err(_) if token.is_empty() => errors.push(self.error(column, token, "missing a number")),
+				Err(_) if token.strip_prefix('-').is_some_and(|x| x.parse::<usize>().is_ok()) => {
+					errors.push(self.error(column, token, "negative numbers aren't allowed"))
+				}
+				Err(_) => errors.push(self.error(column, token, "not an unsigned number"))
+			}
+		}
+		errors.extend(trailing);
+
+		if errors.is_empty() { Ok(numbers) } else { Err(errors) }
+	}
+
+	/// Same as [`Line::tuple`], but it has to be made of one of the `counts` of numbers
+	fn sized(&self, start: usize, end: usize, counts: &[usize], what: &str) -> Result<Vec<(usize, usize)>, Vec<MapParseError>> {
+		let numbers = self.tuple(start, end)?;
+		if counts.contains(&numbers.len()) {
+			return Ok(numbers);
+		}
+
+		let text = &self.text[start..end];
+		let at = start + (text.len() - text.trim_start().len());
+		let expected = counts.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" or ");
+		Err(vec![self.error(at, text.trim(), format!("{} takes {} numbers, not {}", what, expected, numbers.len()))])
+	}
+}
+
+/// Reads the map file format, see README.md
+///
+/// The map can open with a `weights: up=4, left=3, down=1, right=2` line, for the weights of the moves when they're varied.
+/// Blank lines are skipped. Rather than stopping at the first mistake, the whole file is gone through and every mistake is returned.
+impl FromStr for Map<CellType> {
+	type Err = MapParseErrors;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut lines = s.lines()
+			.enumerate()
+			.map(|(i, text)| Line { number: i + 1, text })
+			.filter(|x| !x.text.trim().is_empty())
+			.peekable();
+		let mut errors = vec![];
+		// Where to point at when a section is missing
+		let end = s.lines().count() + 1;
+		let missing = |what: &str| MapParseError { line: end, column: 1, token: String::new(), reason: format!("missing {}", what) };
+
+		let mut weights = CustomMoveWeight::default();
+		if let Some(line) = lines.next_if(|x| x.text.trim_start().starts_with("weights:")) {
+			let at = line.text.find("weights:").expect("just checked") + "weights:".len();
+			match line.text[at..].parse::<CustomMoveWeight>() {
+				Ok(x) => weights = x,
+				Err(e) => errors.push(line.error(at, line.text[at..].trim(), e.to_string()))
+			}
+		}
+
+		// Without the size, the cells can't be checked against it, but the rest is still read for mistakes
+		let size = match lines.next() {
+			Some(line) => match line.sized(0, line.text.len(), &[2], "the grid size") {
+				Ok(size) if size[0].0 == 0 || size[1].0 == 0 => {
+					errors.push(line.error(size[0].1, line.text.trim(), "the grid needs at least a row and a column"));
+					None
+				}
+				Ok(size) if size[0].0.checked_mul(size[1].0).is_none_or(|x| x > MAX_CELLS) => {
+					errors.push(line.error(size[0].1, line.text.trim(), format!("the grid can't have more than {} cells", MAX_CELLS)));
+					None
+				}
+				Ok(size) => Some((size[0].0, size[1].0)),
+				Err(e) => {
+					errors.extend(e);
+					None
+				}
+			}
+			None => {
+				errors.push(missing("the grid size, as in [rows, cols]"));
+				None
+			}
+		};
+
+		// Checks a cell is on the grid, if the size is known
+		let on_grid = |line: &Line, cell: &[(usize, usize)], errors: &mut Vec<MapParseError>| -> Option<(usize, usize)> {
+			let (x, y) = (cell[0].0, cell[1].0);
+			match size {
+				Some((rows, cols)) if x >= cols || y >= rows => {
+					errors.push(line.error(cell[0].1, &format!("({}, {})", x, y), format!("outside the {} by {} grid", rows, cols)));
+					None
+				}
+				_ => Some((x, y))
+			}
+		};
+
+		let initial = match lines.next() {
+			Some(line) => match line.sized(0, line.text.len(), &[2], "the initial cell") {
+				Ok(cell) => on_grid(&line, &cell, &mut errors),
+				Err(e) => {
+					errors.extend(e);
+					None
+				}
+			}
+			None => {
+				errors.push(missing("the initial cell, as in (x, y)"));
+				None
+			}
+		};
+
+		let mut targets = vec![];
+		match lines.next() {
+			Some(line) => {
+				let mut start = 0;
+				for piece in line.text.split('|') {
+					match line.sized(start, start + piece.len(), &[2], "a target") {
+						Ok(cell) => targets.extend(on_grid(&line, &cell, &mut errors)),
+						Err(e) => errors.extend(e)
+					}
+					start += piece.len() + 1;
+				}
+			}
+			None => errors.push(missing("the targets, as in (x, y) | (x, y)"))
+		}
+
+		// Walls, or costlier terrain if the rectangle comes with a cost
+		let mut rectangles = vec![];
+		for line in lines {
+			let rectangle = match line.sized(0, line.text.len(), &[4, 5], "a wall") {
+				Ok(x) => x,
+				Err(e) => {
+					errors.extend(e);
+					continue;
+				}
+			};
+
+			let (x, y, w, h) = (rectangle[0].0, rectangle[1].0, rectangle[2].0, rectangle[3].0);
+			let cost = rectangle.get(4).copied();
+			if let Some((0, at)) = cost {
+				errors.push(line.error(at, "0", "terrain costs start at 1"));
+				continue;
+			}
+
+			if let Some((rows, cols)) = size {
+				if x.checked_add(w).is_none_or(|x| x > cols) || y.checked_add(h).is_none_or(|y| y > rows) {
+					errors.push(line.error(rectangle[0].1, line.text.trim(), format!("reaches outside the {} by {} grid", rows, cols)));
+					continue;
+				}
+			}
+			rectangles.push((x, y, w, h, cost.map(|x| x.0)));
+		}
+
+		let ((rows, cols), initial) = match (size, initial) {
+			(Some(size), Some(initial)) if errors.is_empty() => (size, initial),
+			_ => return Err(MapParseErrors(errors))
+		};
+
+		let mut map = Map {
+			rows,
+			cols,
+			initial,
+			targets: vec![],
+			movement: Movement::default(),
+			weights,
+			values: vec![CellType::Blank(false); rows * cols],
+			terrain: vec![1; rows * cols],
+		};
+
+		let i = map.index(initial);
+		map.values[i] = CellType::Initial(false);
+
+		for target in targets {
+			let i = map.index(target);
+			map.values[i] = CellType::Target;
+			map.targets.push(target);
+		}
+
+		for (ix, iy, dx, dy, cost) in rectangles {
+			for x in ix..dx + ix {
+				for y in iy..dy + iy {
+					let i = map.index((x, y));
+					match cost {
+						Some(cost) => map.terrain[i] = cost,
+						None => map.values[i] = CellType::Wall(false),
+					}
+				}
+			}
+		}
+
+		Ok(map)
+	}
+}
@@ -15,6 +15,48 @@ mod parse {
 		parse_test(&map);
 	}
 
+	/// Line, column and token of each error
+	fn spots(source: &str) -> Vec<(usize, usize, String)> {
+		Map::<CellType>::from_str(source).expect_err("should not parse").0.into_iter()
+			.map(|x| (x.line, x.column, x.token))
+			.collect()
+	}
+
+	#[test]
+	fn every_mistake_is_reported() {
+		let source = "[5, 11]\n( 0, -1)\n(7,0) | (10,3,2) | (12, 1)\n(2,0,2,2) junk\n(8,0,1\n(1,1,1,1,0)\n(9,3,5,1)\n(1,x,1,1)";
+
+		assert_eq!(spots(source), [
+			(2, 6, "-1".to_owned()),
+			(3, 9, "(10,3,2)".to_owned()),
+			(3, 21, "(12, 1)".to_owned()),
+			(4, 11, "junk".to_owned()),
+			(5, 1, "(8,0,1".to_owned()),
+			(6, 10, "0".to_owned()),
+			(7, 2, "(9,3,5,1)".to_owned()),
+			(8, 4, "x".to_owned()),
+		]);
+
+		// Grids too big to count the cells of, or to fit in memory
+		for size in ["[99999999999, 99999999999]", "[100000, 100000]"] {
+			assert_eq!(spots(&format!("{}\n(0, 0)\n(1, 1)", size)), [(1, 2, size.to_owned())]);
+		}
+	}
+
+	#[test]
+	fn numbers_are_checked_before_trailing_text() {
+		assert_eq!(spots("[5, 11]\n(10,-3) x\n(7,0)"), [(2, 5, "-3".to_owned()), (2, 9, "x".to_owned())]);
+	}
+
+	#[test]
+	fn missing_sections_are_reported() {
+		assert_eq!(spots(""), [(1, 1, String::new()), (1, 1, String::new()), (1, 1, String::new())]);
+		assert_eq!(spots("[5, 11]\n(0, 1)\n"), [(3, 1, String::new())]);
+
+		// A bad size still has the rest checked
+		assert_eq!(spots("[0, 11]\n(0, 1, 2)\n(7, 0)"), [(1, 2, "[0, 11]".to_owned()), (2, 1, "(0, 1, 2)".to_owned())]);
+	}
+
 	fn parse_test<T: Debug + Clone>(map: &Map<T>) {
 		format!("{:?}", map).lines().zip([
 			"  XX   TX X",
@@ -27,4 +69,287 @@ mod parse {
 				assert_eq!(gen, *expect);
 			});
 	}
-}
\ No newline at end of file
+}
+
+mod movement {
+	use crate::map::{Map, CellType};
+	use crate::map::movement::{Movement, Corners};
+	use crate::search::Direction;
+	use std::str::FromStr;
+
+	fn neighbours(map: &Map<CellType>, cur: (usize, usize)) -> Vec<(usize, usize)> {
+		map.adjacents(cur).into_iter().map(|x| x.1).collect()
+	}
+
+	#[test]
+	fn hex_rows_are_staggered() {
+		let mut map: Map<CellType> = Map::from_str("[4, 4]\n(0, 0)\n(3, 3)").unwrap();
+		map.movement = Movement::Hex;
+
+		// Even rows reach back a column above and below, odd rows reach forward
+		assert_eq!(neighbours(&map, (1, 2)), [(0, 1), (0, 2), (0, 3), (1, 3), (2, 2), (1, 1)]);
+		assert_eq!(neighbours(&map, (1, 1)), [(1, 0), (0, 1), (1, 2), (2, 2), (2, 1), (2, 0)]);
+
+		// Every move can be undone
+		for (dir, next) in map.adjacents((1, 1)) {
+			assert_eq!(map.adjacent(next, dir.opposite()), Some((1, 1)));
+		}
+	}
+
+	#[test]
+	fn knight_moves_stay_on_the_map() {
+		let mut map: Map<CellType> = Map::from_str("[3, 3]\n(0, 0)\n(2, 2)\n(1, 1, 1, 1)").unwrap();
+		map.movement = Movement::Knight;
+
+		// Jumping over the wall is fine
+		assert_eq!(neighbours(&map, (0, 0)), [(1, 2), (2, 1)]);
+		assert_eq!(map.adjacents((1, 1)), []);
+	}
+
+	#[test]
+	fn corner_rules() {
+		// A wall right of the initial cell and another one below that
+		let mut map: Map<CellType> = Map::from_str("[3, 3]\n(0, 0)\n(2, 2)\n(1, 0, 1, 1)").unwrap();
+
+		let expect = [
+			(Corners::Cut, true, true),
+			(Corners::NoSqueeze, true, true),
+			(Corners::Avoid, false, true),
+		];
+		for (corners, past_one, past_none) in expect {
+			map.movement = Movement::EightWay(corners);
+			assert_eq!(map.clears_corners((0, 0), Direction::DownRight), past_one, "{:?}", corners);
+			assert_eq!(map.clears_corners((0, 1), Direction::DownRight), past_none, "{:?}", corners);
+		}
+
+		*map.read_cell_mut((0, 1)) = CellType::Wall(false);
+		for (corners, squeeze) in [(Corners::Cut, true), (Corners::NoSqueeze, false), (Corners::Avoid, false)] {
+			map.movement = Movement::EightWay(corners);
+			assert_eq!(map.clears_corners((0, 0), Direction::DownRight), squeeze, "{:?}", corners);
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "eyecandy")]
+	fn diagonals_are_drawn() {
+		let mut map: Map<CellType> = Map::from_str("[4, 4]\n(0, 0)\n(3, 0)").unwrap();
+		map.movement = Movement::EightWay(Corners::Avoid);
+		map.draw_path(&vec![Direction::DownRight, Direction::DownRight, Direction::UpRight, Direction::Up]);
+
+		assert_eq!(format!("{:?}", map).lines().collect::<Vec<_>>(), [
+			"I  T",
+			" \\ *",
+			"  * ",
+			"    ",
+		]);
+	}
+}
+
+mod terrain {
+	use crate::map::{Map, CellType};
+	use std::str::FromStr;
+
+	#[test]
+	fn rectangles_with_a_cost_are_terrain() {
+		let map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(3, 2)\n(1, 0, 1, 2)\n(2, 1, 2, 2, 3)").unwrap();
+
+		assert_eq!(map.read_cell((1, 1)), &CellType::Wall(false));
+		assert_eq!(map.read_cell((2, 1)), &CellType::Blank(false));
+		assert_eq!(map.read_cell((3, 2)), &CellType::Target);
+		assert_eq!([map.terrain((2, 1)), map.terrain((3, 2)), map.terrain((2, 0)), map.terrain((1, 1))], [3, 3, 1, 1]);
+		assert!(map.has_terrain());
+
+		assert!(Map::from_str("[3, 4]\n(0, 0)\n(3, 2)\n(2, 1, 2, 2, 0)").is_err());
+	}
+
+	#[test]
+	fn scattered_terrain_costs_more() {
+		let mut map = Map::random_maze(20, 20, 1);
+		assert!(!map.has_terrain());
+
+		map.scatter_terrain(5, 3);
+		assert!(map.has_terrain());
+		for y in 0..map.rows {
+			for x in 0..map.cols {
+				assert!((1..=3).contains(&map.terrain((x, y))));
+			}
+		}
+	}
+}
+
+mod weights {
+	use crate::map::{Map, CellType};
+	use crate::search::{CustomMoveWeight, MoveWeight, Direction};
+	use std::str::FromStr;
+
+	#[test]
+	fn header_sets_the_weights() {
+		let map: Map<CellType> = Map::from_str("weights: up=2, down=5\n[2, 3]\n(0, 0)\n(2, 1)").unwrap();
+		assert_eq!(map.weights, CustomMoveWeight { up: 2, left: 3, down: 5, right: 2 });
+		assert_eq!(map.weights.weigh(&Direction::DownLeft), 8);
+
+		let map: Map<CellType> = Map::from_str("[2, 3]\n(0, 0)\n(2, 1)").unwrap();
+		assert_eq!(map.weights, CustomMoveWeight::default());
+
+		assert!(Map::from_str("weights: up=two\n[2, 3]\n(0, 0)\n(2, 1)").is_err());
+		assert!(Map::from_str("weights: sideways=1\n[2, 3]\n(0, 0)\n(2, 1)").is_err());
+	}
+
+	#[test]
+	fn weights_read_back_the_way_they_are_written() {
+		let weights = CustomMoveWeight { up: 1, left: 7, down: 2, right: 3 };
+		assert_eq!(weights.to_string().parse::<CustomMoveWeight>().unwrap(), weights);
+	}
+}
+
+mod validate {
+	use crate::map::{Map, CellType};
+	use crate::map::validate::{MapIssue, Severity};
+	use std::str::FromStr;
+
+	#[test]
+	fn walls_over_the_initial_cell_and_targets_are_errors() {
+		let map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(3, 2) | (1, 1) | (3, 2)\n(0, 0, 1, 1)\n(1, 1, 1, 1)").unwrap();
+
+		assert_eq!(map.validate(), [
+			MapIssue::InitialWalled((0, 0)),
+			MapIssue::TargetWalled((1, 1)),
+			MapIssue::DuplicateTarget((3, 2)),
+		]);
+		assert_eq!(map.validate().iter().map(|x| x.severity()).collect::<Vec<_>>(), [Severity::Error, Severity::Error, Severity::Warning]);
+	}
+
+	#[test]
+	fn walled_targets_listed_twice_are_reported_once() {
+		let map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(1, 1) | (3, 2) | (1, 1)\n(1, 1, 1, 1)").unwrap();
+
+		assert_eq!(map.validate(), [
+			MapIssue::TargetWalled((1, 1)),
+			MapIssue::DuplicateTarget((1, 1)),
+		]);
+	}
+
+	#[test]
+	fn hand_made_maps_are_checked_too() {
+		let mut map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(3, 2)\n(2, 0, 1, 3)").unwrap();
+		assert_eq!(map.validate(), [MapIssue::Unreachable]);
+
+		map.targets = vec![(4, 0)];
+		map.initial = (0, 3);
+		assert_eq!(map.validate(), [
+			MapIssue::InitialOffGrid((0, 3)),
+			MapIssue::TargetOffGrid((4, 0)),
+			MapIssue::UnlistedTarget((3, 2)),
+		]);
+
+		map.targets.clear();
+		assert!(map.validate().contains(&MapIssue::NoTargets));
+	}
+
+	#[test]
+	fn random_mazes_have_distinct_targets() {
+		for _ in 0..20 {
+			let map = Map::random_maze(3, 3, 10);
+			assert_eq!(map.targets.len(), 8);
+			assert!(map.validate().iter().all(|x| *x == MapIssue::Unreachable), "{:?}", map.validate());
+		}
+	}
+}
+
+mod ascii {
+	use crate::map::{Map, CellType};
+	use crate::map::ascii::Legend;
+	use std::str::FromStr;
+
+	#[test]
+	fn reads_back_what_it_draws() {
+		let mut map: Map<CellType> = Map::from_str(include_str!("RobotNav-test.txt")).unwrap();
+		map.set_terrain((4, 2), 3);
+
+		let text = map.to_ascii(&Legend::default()).unwrap();
+		assert_eq!(text.lines().nth(2), Some("....3......"));
+
+		let read = Map::from_ascii(&text, &Legend::default()).unwrap();
+		assert_eq!(read.to_rect(), map.to_rect());
+	}
+
+	#[test]
+	fn legend_is_configurable() {
+		let legend: Legend = "initial=S, target=G,wall=#".parse().unwrap();
+		// Short lines are made up with open ground
+		let map = Map::from_ascii("S.#\n  #G\n\n", &legend).unwrap();
+
+		assert_eq!((map.rows, map.cols, map.initial), (2, 4, (0, 0)));
+		assert_eq!(map.targets, [(3, 1)]);
+		assert_eq!(format!("{:?}", map), "I X \n  XT\n");
+
+		assert!("wall=2".parse::<Legend>().is_err());
+		assert!("wall=T".parse::<Legend>().is_err());
+		assert!("door=D".parse::<Legend>().is_err());
+	}
+
+	#[test]
+	fn mistakes_are_pointed_at() {
+		let errors = Map::from_ascii("I..\n.?I\n", &Legend::default()).expect_err("should not parse").0;
+		assert_eq!(errors.iter().map(|x| (x.line, x.column, x.token.as_ref())).collect::<Vec<_>>(), [(2, 2, "?"), (2, 3, "I")]);
+
+		let errors = Map::from_ascii("..T\n", &Legend::default()).expect_err("should not parse").0;
+		assert_eq!((errors[0].line, errors[0].column), (2, 1));
+	}
+}
+
+mod occupancy {
+	use crate::map::{Map, CellType};
+	use crate::map::occupancy::{Occupancy, MapServer};
+
+	#[test]
+	fn plain_and_binary_images_agree() {
+		let plain = Map::from_pnm(b"P2\n# black, grey and white\n3 2\n255\n0 128 255\n255 255 0\n", &Occupancy::default()).unwrap();
+		let mut binary = b"P5 3 2 255\n".to_vec();
+		binary.extend([0, 128, 255, 255, 255, 0]);
+		let binary = Map::from_pnm(&binary, &Occupancy::default()).unwrap();
+		// Two bytes a sample past 255, with the colours averaged
+		let mut colour = b"P6\n3 2\n65535\n".to_vec();
+		for (r, g, b) in [(0, 0, 0), (65535, 0, 65535), (65535, 65535, 65535), (65535, 65535, 65535), (65535, 65535, 65535), (0, 0, 0)] {
+			for x in [r, g, b] {
+				colour.extend(u16::to_be_bytes(x));
+			}
+		}
+		let colour = Map::from_pnm(&colour, &Occupancy::default()).unwrap();
+
+		for map in [&plain, &binary, &colour] {
+			assert_eq!(format!("{:?}", map), "XXI\n  X\n");
+		}
+		assert_eq!(format!("{:?}", Map::from_pnm(b"P3 3 2 1 0 0 0 1 0 1 1 1 1 1 1 1 1 1 1 0 0 0", &Occupancy::default()).unwrap()), "XXI\n  X\n");
+
+		// Unknown cells can be let through, and the shades turned around
+		let occupancy = Occupancy { negate: true, unknown: CellType::Blank(false), ..Occupancy::default() };
+		assert_eq!(format!("{:?}", Map::from_pnm(b"P2 3 2 255 0 128 255 255 255 0", &occupancy).unwrap()), "I X\nXX \n");
+
+		assert!(Map::from_pnm(b"P5 3 2 255\n\0\0", &Occupancy::default()).is_err());
+		assert!(Map::from_pnm(b"P2 1 1 255 256", &Occupancy::default()).is_err());
+		assert!(Map::from_pnm(b"P1 1 1 0", &Occupancy::default()).is_err());
+		// Sizes that don't fit in a usize, in samples and then in bytes
+		for image in [&b"P5 4294967296 4294967297 255"[..], b"P6 4611686018427387904 1 65535"] {
+			assert!(Map::from_pnm(image, &Occupancy::default()).unwrap_err().0.contains("too big"));
+		}
+	}
+
+	#[test]
+	fn world_metres_are_converted_to_cells() {
+		let server: MapServer = "image: \"room.pgm\"\nresolution: 0.5\n\n# the bottom left corner\norigin: [-1.0, -1.0, 0.0]\nnegate: 1\nmode: trinary\n".parse().unwrap();
+		assert_eq!(server.image, "room.pgm");
+		assert!(server.occupancy.negate);
+
+		let map = Map::from_pnm(b"P2 5 3 1 1 1 1 1 1 1 1 1 1 1 1 1 1 1 1", &Occupancy::default()).unwrap();
+		// The world goes up from the bottom row
+		assert_eq!(server.cell(&map, (-1.0, -1.0)), Some((0, 2)));
+		assert_eq!(server.cell(&map, (1.25, 0.4)), Some((4, 0)));
+		assert_eq!(server.cell(&map, (1.5, 0.0)), None);
+		assert_eq!(server.cell(&map, (0.0, -1.1)), None);
+		assert_eq!(server.world(&map, (4, 0)), (1.25, 0.25));
+
+		let errors = "image: room.pgm\nresolution: 0\nfree_thresh: 2\nmode: raw".parse::<MapServer>().expect_err("should not parse").0;
+		assert_eq!(errors.iter().map(|x| (x.line, x.column)).collect::<Vec<_>>(), [(2, 13), (3, 14), (4, 7), (5, 1)]);
+	}
+}
@@ -0,0 +1,164 @@
+use crate::map::{Map, CellType};
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+
+/// How bad a [`MapIssue`] is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+	/// The map can still be searched, but probably isn't what was meant
+	Warning,
+	/// Searching the map would go wrong, or panic
+	Error,
+}
+
+impl Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Severity::Warning => write!(f, "warning"),
+			Severity::Error => write!(f, "error"),
+		}
+	}
+}
+
+/// Something off about a map, see [`Map::validate`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapIssue {
+	/// The grid has no cells
+	Empty,
+	/// The initial cell is outside the grid
+	InitialOffGrid((usize, usize)),
+	/// A target is outside the grid
+	TargetOffGrid((usize, usize)),
+	/// A wall has been put over the initial cell
+	InitialWalled((usize, usize)),
+	/// A wall has been put over a target, which is still listed as one
+	TargetWalled((usize, usize)),
+	/// A cell tagged as a target that isn't in the list of targets, so the heuristics don't know about it
+	UnlistedTarget((usize, usize)),
+	/// There's nothing to search for
+	NoTargets,
+	/// The same target is listed more than once
+	DuplicateTarget((usize, usize)),
+	/// The initial cell is a target, so there's nowhere to go
+	TargetOnInitial((usize, usize)),
+	/// None of the targets can be reached from the initial cell with the moves of the map
+	Unreachable,
+}
+
+impl MapIssue {
+	pub fn severity(&self) -> Severity {
+		match self {
+			MapIssue::DuplicateTarget(_) | MapIssue::TargetOnInitial(_) | MapIssue::Unreachable => Severity::Warning,
+			_ => Severity::Error,
+		}
+	}
+}
+
+impl Display for MapIssue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MapIssue::Empty => write!(f, "the grid has no cells"),
+			MapIssue::InitialOffGrid((x, y)) => write!(f, "the initial cell ({}, {}) is outside the grid", x, y),
+			MapIssue::TargetOffGrid((x, y)) => write!(f, "the target ({}, {}) is outside the grid", x, y),
+			MapIssue::InitialWalled((x, y)) => write!(f, "the initial cell ({}, {}) is covered by a wall", x, y),
+			MapIssue::TargetWalled((x, y)) => write!(f, "the target ({}, {}) is covered by a wall", x, y),
+			MapIssue::UnlistedTarget((x, y)) => write!(f, "({}, {}) is marked as a target but isn't listed as one", x, y),
+			MapIssue::NoTargets => write!(f, "there are no targets"),
+			MapIssue::DuplicateTarget((x, y)) => write!(f, "the target ({}, {}) is listed more than once", x, y),
+			MapIssue::TargetOnInitial((x, y)) => write!(f, "the initial cell ({}, {}) is a target", x, y),
+			MapIssue::Unreachable => write!(f, "none of the targets can be reached from the initial cell"),
+		}
+	}
+}
+
+impl Map<CellType> {
+	/// Goes over the map for anything that would trip up the searches, in the order of [`MapIssue`]
+	///
+	/// The errors come from maps put together by hand, since the parser doesn't let most of them through,
+	/// while the warnings are left for the caller to decide on. Whether the targets can be reached depends on the [`Movement`](crate::map::movement::Movement) of the map.
+	pub fn validate(&self) -> Vec<MapIssue> {
+		let mut issues = vec![];
+		if self.rows == 0 || self.cols == 0 {
+			issues.push(MapIssue::Empty);
+		}
+
+		let on_grid = |(x, y): (usize, usize)| x < self.cols && y < self.rows;
+		let initial = on_grid(self.initial);
+		if !initial {
+			issues.push(MapIssue::InitialOffGrid(self.initial));
+		}
+		let targets: Vec<(usize, usize)> = self.targets.iter().copied().filter(|x| on_grid(*x)).collect();
+		for target in self.targets.iter().filter(|x| !on_grid(**x)) {
+			issues.push(MapIssue::TargetOffGrid(*target));
+		}
+
+		if initial {
+			if let CellType::Wall(_) = self.read_cell(self.initial) {
+				issues.push(MapIssue::InitialWalled(self.initial));
+			}
+		}
+		for (i, target) in targets.iter().enumerate() {
+			// Once per target, the repeats are warned about further down
+			if targets[..i].contains(target) {
+				continue;
+			}
+			if let CellType::Wall(_) = self.read_cell(*target) {
+				issues.push(MapIssue::TargetWalled(*target));
+			}
+		}
+
+		for y in 0..self.rows {
+			for x in 0..self.cols {
+				if let CellType::Target = self.read_cell((x, y)) {
+					if !targets.contains(&(x, y)) {
+						issues.push(MapIssue::UnlistedTarget((x, y)));
+					}
+				}
+			}
+		}
+
+		if self.targets.is_empty() {
+			issues.push(MapIssue::NoTargets);
+		}
+		for (i, target) in targets.iter().enumerate() {
+			// Only the first repeat of each target
+			let repeats = targets[..i].iter().filter(|x| *x == target).count();
+			if repeats == 1 {
+				issues.push(MapIssue::DuplicateTarget(*target));
+			}
+		}
+
+		if initial && targets.contains(&self.initial) {
+			issues.push(MapIssue::TargetOnInitial(self.initial));
+		} else if initial && !targets.is_empty() && !self.reaches_target() {
+			issues.push(MapIssue::Unreachable);
+		}
+
+		return issues;
+	}
+
+	/// Breadth-first flood from the initial cell, stopping at the first target
+	fn reaches_target(&self) -> bool {
+		let mut seen = vec![false; self.rows * self.cols];
+		let mut queue = VecDeque::from([self.initial]);
+		seen[self.index(self.initial)] = true;
+
+		while let Some(cur) = queue.pop_front() {
+			for (_, next) in self.adjacents(cur) {
+				let i = self.index(next);
+				if seen[i] {
+					continue;
+				}
+				seen[i] = true;
+
+				match self.read_cell(next) {
+					CellType::Target => return true,
+					CellType::Wall(_) => {}
+					_ => queue.push_back(next)
+				}
+			}
+		}
+
+		return false;
+	}
+}
@@ -0,0 +1,222 @@
+use crate::search::Direction;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::{SearchLimits, Limit};
+use crate::utils::visited::Visited;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The outcome of running a [`Search`](crate::search::Search) to completion
+#[derive(Clone, Debug)]
+pub struct SearchReport {
+	/// Number of nodes taken off the frontier during the search
+	pub search_nodes: usize,
+	/// The list of directions leading from the initial cell to a target, if any was found
+	///
+	/// Without a solution, [`SearchReport::cut_off`] tells whether there's none at all or the search was stopped short
+	pub solution: Option<Vec<Direction>>,
+	/// Cost of the solution under the [`MoveWeight`] the search was run with
+	pub cost: Option<usize>,
+	/// The target the solution leads to
+	pub target: Option<(usize, usize)>,
+	/// The cells the search has visited, the [`Map`](crate::map::Map) itself is left untouched
+	pub visited: Visited,
+	/// Number of nodes put onto the frontier, including the initial node
+	pub generated: usize,
+	/// Number of nodes that had their neighbours put onto the frontier
+	pub expanded: usize,
+	/// Number of nodes thrown away for being a repeated state
+	pub pruned: usize,
+	/// The most nodes the frontier had held at once
+	pub peak_frontier: usize,
+	/// Wall time spent searching
+	pub elapsed: Duration,
+	/// The cells in the order they were expanded, only kept if asked for with
+	/// [`Search::record_expansions`](crate::search::Search::record_expansions)
+	pub expanded_cells: Option<Vec<(usize, usize)>>,
+	/// The limit that stopped the search short of finding a solution, [`None`] if it ran its course
+	///
+	/// Anytime searches can be stopped short of improving on a solution they've already found, in which case they have both
+	pub cut_off: Option<Limit>,
+	/// The cell where the halves of a bidirectional search met up, [`None`] for the other searches
+	pub meeting: Option<(usize, usize)>,
+	/// How many times over the cheapest path the solution costs at most, for the anytime searches
+	pub suboptimality: Option<f64>,
+}
+
+/// Running statistics of a search, which is turned into a [`SearchReport`] when the search finishes
+///
+/// It also buffers the [`SearchEvent`]s raised along the way,
+/// for the searches to hand out one [`Search::step`](crate::search::Search::step) at a time
+pub struct Tally {
+	started: Option<Instant>,
+	finished: bool,
+	pub search_nodes: usize,
+	pub generated: usize,
+	pub expanded: usize,
+	pub pruned: usize,
+	pub peak_frontier: usize,
+	pub meeting: Option<(usize, usize)>,
+	pub suboptimality: Option<f64>,
+	expanded_cells: Option<Vec<(usize, usize)>>,
+	events: VecDeque<SearchEvent>,
+	limits: SearchLimits,
+	/// Set once a path has been left unexplored for breaking the depth or cost limit
+	bounded: Option<Limit>,
+}
+
+impl Tally {
+	/// A tally for a search that's yet to start
+	pub fn idle() -> Tally {
+		Tally {
+			started: None,
+			finished: false,
+			search_nodes: 0,
+			generated: 0,
+			expanded: 0,
+			pruned: 0,
+			peak_frontier: 0,
+			meeting: None,
+			suboptimality: None,
+			expanded_cells: None,
+			events: VecDeque::new(),
+			limits: SearchLimits::default(),
+			bounded: None,
+		}
+	}
+
+	/// Starts the clock, `record` decides whether the expanded cells are kept
+	pub fn start(record: bool, limits: SearchLimits) -> Tally {
+		Tally {
+			started: Some(Instant::now()),
+			expanded_cells: if record { Some(vec![]) } else { None },
+			limits,
+			..Tally::idle()
+		}
+	}
+
+	pub fn is_idle(&self) -> bool {
+		self.started.is_none()
+	}
+
+	/// Whether the search has come to an end, there could still be events left to hand out
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	/// Takes the oldest event that's yet to be handed out
+	pub fn next_event(&mut self) -> Option<SearchEvent> {
+		self.events.pop_front()
+	}
+
+	/// Checks the node budget, deadline and cancellation flag, see [`SearchLimits::overrun`]
+	pub fn overrun(&self) -> Option<Limit> {
+		self.limits.overrun(self.expanded)
+	}
+
+	/// Whether a node `depth` moves and `cost` away from the initial cell is within the limits
+	///
+	/// Nodes that aren't are left unexplored, which is remembered so that running out of nodes is reported as a cut off
+	pub fn within(&mut self, depth: usize, cost: usize) -> bool {
+		match self.limits.exceeded(depth, cost) {
+			Some(limit) => {
+				self.bounded.get_or_insert(limit);
+				false
+			}
+			None => true
+		}
+	}
+
+	/// Counts a node put onto a frontier which now has `frontier` nodes in it
+	pub fn generate(&mut self, cell: (usize, usize), frontier: usize) {
+		self.generated += 1;
+		self.peak_frontier = self.peak_frontier.max(frontier);
+		self.events.push_back(SearchEvent::Generated(cell));
+	}
+
+	/// Counts a node that's had its neighbours explored
+	pub fn expand(&mut self, cell: (usize, usize)) {
+		self.expanded += 1;
+		if let Some(cells) = &mut self.expanded_cells {
+			cells.push(cell);
+		}
+		self.events.push_back(SearchEvent::Expanded(cell));
+	}
+
+	/// Counts a node thrown away for being a repeated state
+	pub fn prune(&mut self, cell: (usize, usize)) {
+		self.pruned += 1;
+		self.events.push_back(SearchEvent::Pruned(cell));
+	}
+
+	/// Ends the search by reaching `target` along `path`, which costs `cost`
+	pub fn found(&mut self, cost: usize, target: (usize, usize), path: Vec<Direction>, visited: &Visited) {
+		let report = self.report(Some((target, path, cost)), visited);
+		self.events.push_back(SearchEvent::Found(report));
+	}
+
+	/// Hands out a solution without ending the search, for the anytime searches that go on to improve on it
+	pub fn improved(&mut self, cost: usize, target: (usize, usize), path: Vec<Direction>, visited: &Visited) {
+		let report = self.write_up(Some((target, path, cost)), visited, self.expanded_cells.clone());
+		self.events.push_back(SearchEvent::Improved(report));
+	}
+
+	/// Ends the search for having run into the `limit`, but with a solution in hand from before
+	pub fn settled(&mut self, limit: Limit, cost: usize, target: (usize, usize), path: Vec<Direction>, visited: &Visited) {
+		let mut report = self.report(Some((target, path, cost)), visited);
+		report.cut_off = Some(limit);
+		self.events.push_back(SearchEvent::Found(report));
+	}
+
+	/// Ends the search for having run out of nodes to explore
+	///
+	/// It's a cut off rather than an exhaustion if any node was left unexplored for breaking the limits
+	pub fn exhausted(&mut self, visited: &Visited) {
+		match self.bounded {
+			Some(limit) => self.cut_off(visited, limit),
+			None => {
+				let report = self.report(None, visited);
+				self.events.push_back(SearchEvent::Exhausted(report));
+			}
+		}
+	}
+
+	/// Ends the search for having run into the `limit`
+	pub fn cut_off(&mut self, visited: &Visited, limit: Limit) {
+		let mut report = self.report(None, visited);
+		report.cut_off = Some(limit);
+		self.events.push_back(SearchEvent::CutOff(report));
+	}
+
+	/// Stops the clock and writes up the report
+	fn report(&mut self, solution: Option<((usize, usize), Vec<Direction>, usize)>, visited: &Visited) -> SearchReport {
+		self.finished = true;
+
+		let expanded_cells = self.expanded_cells.take();
+		self.write_up(solution, visited, expanded_cells)
+	}
+
+	/// Writes up the report as things stand
+	fn write_up(&self, solution: Option<((usize, usize), Vec<Direction>, usize)>, visited: &Visited, expanded_cells: Option<Vec<(usize, usize)>>) -> SearchReport {
+		let (target, solution, cost) = match solution {
+			Some((target, path, cost)) => (Some(target), Some(path), Some(cost)),
+			None => (None, None, None)
+		};
+
+		SearchReport {
+			search_nodes: self.search_nodes,
+			cost,
+			solution,
+			target,
+			visited: visited.clone(),
+			generated: self.generated,
+			expanded: self.expanded,
+			pruned: self.pruned,
+			peak_frontier: self.peak_frontier,
+			elapsed: self.started.map(|x| x.elapsed()).unwrap_or_default(),
+			expanded_cells,
+			cut_off: None,
+			meeting: self.meeting,
+			suboptimality: self.suboptimality,
+		}
+	}
+}
@@ -0,0 +1,468 @@
+use crate::map::{Map, CellType};
+use crate::map::movement::{Movement, Corners};
+use crate::map::format::MapFormat;
+use crate::map::ascii::Legend;
+use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight, CustomMoveWeight, EuclideanMoveWeight};
+use crate::search::limits::SearchLimits;
+use crate::search::dfs::DepthFirst;
+use crate::search::bfs::BreadthFirst;
+use crate::search::iddfs::IterativeDeepening;
+use crate::search::idastar::IterativeDeepeningAStar;
+use crate::search::bidirectional::Bidirectional;
+use crate::search::jps::{self, JumpPoint};
+use crate::search::gbfs::GBFCursor;
+use crate::search::astar::AStarCursor;
+use crate::search::wastar::{Inflated, DEFAULT_WEIGHT};
+use crate::search::arastar::AnytimeRepairingAStar;
+use crate::search::dijkstra::DijkstraCursor;
+use crate::utils::heuristics::{Heuristic, DefaultHeuristic, CustomManhattan, ManhattanHeuristic, EuclideanHeuristic, OctileHeuristic, ChebyshevHeuristic, Scaled, LandmarkHeuristic};
+use crate::utils::landmarks::{self, Landmarks, Selection};
+use crate::utils::distances::DistanceField;
+use crate::utils::check::{check_heuristic, HeuristicCheck};
+use crate::SearchReport;
+use crate::tour::plan_tour;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use rand::random;
+
+/// Runtime configuration of a search run, as assembled by the command line interface
+#[derive(Clone, Default)]
+pub struct Config {
+	// The input map file, can be RANDOM
+	pub map_file: String,
+	// Search method, refer to docs
+	pub method: String,
+	// Where to copy the map to as a save, useful for testing with random
+	pub save_map: Option<String>,
+	// Variable move weight experiment (ie. the weights of moving in different direction changes)
+	pub var_move_wt: bool,
+	// The follow relates to randomly generated maps
+	pub rand_size: Option<(usize, usize)>,
+	pub target_count: Option<usize>,
+	// Budgets of the search, see SearchLimits
+	pub max_nodes: Option<usize>,
+	pub max_depth: Option<usize>,
+	pub timeout: Option<Duration>,
+	// Plan a route through every target instead of stopping at the first
+	pub visit_all: bool,
+	// The moves that can be made between cells, put onto the map as it's loaded
+	pub movement: Movement,
+	// Number of patches of costlier terrain to scatter over the map as it's loaded
+	pub terrain: Option<usize>,
+	// Weights of the moves put onto the map as it's loaded, in place of the ones it came with
+	pub move_weights: Option<CustomMoveWeight>,
+	// Heuristic of the informed searches, in place of the one that goes with the map
+	pub heuristic: Option<HeuristicKind>,
+	// Where the landmark tables are kept between runs on the same map
+	pub landmark_file: Option<String>,
+	// Inflation of the heuristic for weighted A*, and for the first pass of ARA*
+	pub weight: Option<f64>,
+	// Refuse maps that Map::validate has any warnings about, not just errors
+	pub strict: bool,
+	// How the map file is written down, and how the map is saved unless save_format says otherwise
+	pub format: MapFormat,
+	pub save_format: Option<MapFormat>,
+	// Characters of the cells in the ASCII art format
+	pub legend: Legend,
+	// Give IDA* a cost per cell to remember, rather than the memory-light search
+	pub cost_table: bool,
+	// The following relates to ROS map_server maps, with the initial cell and targets given in world metres
+	pub start: Option<(f64, f64)>,
+	pub goals: Vec<(f64, f64)>,
+	// What the cells that are neither occupied nor free are taken as, walls if not given
+	pub unknown: Option<CellType>,
+}
+
+impl Config {
+	pub fn rand_size(&self) -> (usize, usize) {
+		self.rand_size.unwrap_or((random::<usize>() % 16 + 16, random::<usize>() % 112 + 16))
+	}
+
+	pub fn target_count(&self) -> usize {
+		self.target_count.unwrap_or(2)
+	}
+
+	pub fn weight(&self) -> f64 {
+		self.weight.unwrap_or(DEFAULT_WEIGHT)
+	}
+
+	/// The limits of a search that starts now, the timeout is counted from this call
+	pub fn limits(&self) -> SearchLimits {
+		SearchLimits {
+			max_nodes: self.max_nodes,
+			max_depth: self.max_depth,
+			deadline: self.timeout.map(|x| Instant::now() + x),
+			..SearchLimits::default()
+		}
+	}
+
+	/// A warning for when the heuristic asked for might lead the method to a path that isn't the cheapest on the map
+	pub fn heuristic_warning(&self, map: &Map<CellType>) -> Option<String> {
+		let heuristic = self.heuristic?;
+		let method = Method::from_str(&self.method).ok()?;
+
+		if method.needs_admissible() && !heuristic.admissible(map, self.var_move_wt) {
+			return Some(format!("{:?} can overestimate with {:?} movement, so {:?} might not find the cheapest path", heuristic, map.movement, method));
+		}
+		None
+	}
+}
+
+/// The search algorithms that are available, see README.md for the names they're known by
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+	BreadthFirst,
+	DepthFirst,
+	GreedyBestFirst,
+	AStar,
+	IterativeDeepening,
+	WeightedAStar,
+	Dijkstra,
+	IterativeDeepeningAStar,
+	BidirectionalBreadthFirst,
+	BidirectionalAStar,
+	JumpPoint,
+	AnytimeRepairingAStar,
+}
+
+/// Error for when a search method name is not recognised
+#[derive(Debug)]
+pub struct UnknownMethod(pub String);
+
+impl Display for UnknownMethod {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unrecognized search method: {}", self.0)
+	}
+}
+
+impl Error for UnknownMethod {}
+
+impl FromStr for Method {
+	type Err = UnknownMethod;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_ascii_uppercase().as_ref() {
+			"DFS" => Method::DepthFirst,
+			"BFS" => Method::BreadthFirst,
+			"GBFS" => Method::GreedyBestFirst,
+			"AS" | "ASTAR" => Method::AStar,
+			"CUS1" | "IDDFS" => Method::IterativeDeepening,
+			"CUS2" | "WAS" | "WASTAR" | "WEIGHTED_ASTAR" => Method::WeightedAStar,
+			"UCS" | "DIJKSTRA" => Method::Dijkstra,
+			"IDAS" | "IDASTAR" => Method::IterativeDeepeningAStar,
+			"BIBFS" => Method::BidirectionalBreadthFirst,
+			"BIAS" | "BIASTAR" => Method::BidirectionalAStar,
+			"JPS" => Method::JumpPoint,
+			"ARA" | "ARASTAR" => Method::AnytimeRepairingAStar,
+			x => return Err(UnknownMethod(x.to_owned()))
+		})
+	}
+}
+
+/// Expands `$body` with `$w` standing for the move weigher that goes with the map and `var_move_wt`, and `$weigher` for one of them:
+/// the [`CustomMoveWeight`] of the map if asked for, [`EuclideanMoveWeight`] for eight-way movement, otherwise [`UniformMoveWeight`]
+macro_rules! weighed {
+	($map:expr, $var_move_wt:expr, $w:ident, $weigher:ident => $body:expr) => {
+		if $var_move_wt {
+			type $w = CustomMoveWeight;
+			let $weigher: $w = $map.weights;
+			$body
+		} else if let Movement::EightWay(_) = $map.movement {
+			type $w = EuclideanMoveWeight;
+			let $weigher = $w::default();
+			$body
+		} else {
+			type $w = UniformMoveWeight;
+			let $weigher = $w::default();
+			$body
+		}
+	};
+}
+
+/// The heuristics that can be picked by name, see README.md for the names they're known by
+///
+/// They're built in the units of the weigher that [`Method::build`] picks, so they can be swapped in for one another
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HeuristicKind {
+	Manhattan,
+	Euclidean,
+	Octile,
+	Chebyshev,
+	Zero,
+	Landmark,
+	Exact,
+}
+
+/// Error for when a heuristic name is not recognised
+#[derive(Debug)]
+pub struct UnknownHeuristic(pub String);
+
+impl Display for UnknownHeuristic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unrecognized heuristic: {}", self.0)
+	}
+}
+
+impl Error for UnknownHeuristic {}
+
+impl FromStr for HeuristicKind {
+	type Err = UnknownHeuristic;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s.to_ascii_lowercase().as_ref() {
+			"manhattan" => HeuristicKind::Manhattan,
+			"euclidean" => HeuristicKind::Euclidean,
+			"octile" => HeuristicKind::Octile,
+			"chebyshev" => HeuristicKind::Chebyshev,
+			"zero" => HeuristicKind::Zero,
+			"landmark" | "alt" => HeuristicKind::Landmark,
+			"exact" => HeuristicKind::Exact,
+			x => return Err(UnknownHeuristic(x.to_owned()))
+		})
+	}
+}
+
+/// Picks the `init` or the `from_initial` of a heuristic, depending on which way the search goes
+macro_rules! aimed {
+	($h:ident, $map:expr, $backwards:expr) => {
+		if $backwards { $h::from_initial($map) } else { $h::init($map) }
+	};
+}
+
+/// Weights of the cheapest straight and the cheapest diagonal move
+fn cheapest<W: MoveWeight>(weigher: &W) -> (usize, usize) {
+	let directions = Movement::EightWay(Corners::default()).directions();
+	let cheapest = |dirs: &[Direction]| dirs.iter().map(|d| weigher.weigh(d)).min().expect("no directions");
+
+	return (cheapest(&directions[..4]), cheapest(&directions[4..]));
+}
+
+impl HeuristicKind {
+	/// The heuristic picked when none is asked for, which never overestimates for the movement of the map
+	pub fn for_map(map: &Map<CellType>, var_move_wt: bool) -> HeuristicKind {
+		// The custom weights of every move add up to at least the weights of the steps it covers along each axis
+		if var_move_wt {
+			return HeuristicKind::Manhattan;
+		}
+
+		match map.movement {
+			Movement::FourWay => HeuristicKind::Manhattan,
+			Movement::EightWay(_) => HeuristicKind::Octile,
+			Movement::Hex | Movement::Knight => HeuristicKind::Chebyshev,
+		}
+	}
+
+	/// Whether the heuristic never overestimates on the map, with the weigher out of `weighed!`
+	///
+	/// Terrain only ever makes moves cost more, so it doesn't come into it
+	pub fn admissible(&self, map: &Map<CellType>, var_move_wt: bool) -> bool {
+		match self {
+			// The landmark tables and the distance field are worked out over the map with the same weigher
+			HeuristicKind::Zero | HeuristicKind::Chebyshev | HeuristicKind::Landmark | HeuristicKind::Exact => true,
+			// Every custom move weighs at least as much as its steps along each axis
+			_ if var_move_wt => true,
+			HeuristicKind::Manhattan => map.movement == Movement::FourWay,
+			// Knight moves cover more than a cell for the weight of one
+			HeuristicKind::Octile | HeuristicKind::Euclidean => map.movement != Movement::Knight,
+		}
+	}
+
+	/// Builds the heuristic over the map, in the units of the weigher out of `weighed!`
+	///
+	/// `backwards` estimates the cost from the initial cell instead, for searching backwards from the targets
+	pub fn build(&self, map: &Map<CellType>, var_move_wt: bool, backwards: bool) -> Box<dyn Heuristic> {
+		let (straight, diagonal) = weighed!(map, var_move_wt, W, weigher => cheapest(&weigher));
+		// Knight moves go up to two cells along an axis
+		let reach = if map.movement == Movement::Knight { 2 } else { 1 };
+
+		match self {
+			HeuristicKind::Manhattan if var_move_wt => Box::new(aimed!(CustomManhattan, map, backwards)),
+			HeuristicKind::Manhattan => Box::new(Scaled::init(aimed!(ManhattanHeuristic, map, backwards), straight)),
+			// As wide as a straight move, and no more than a diagonal move over the length of the diagonal
+			HeuristicKind::Euclidean => {
+				let scale = (straight as f64).min(diagonal as f64 / std::f64::consts::SQRT_2);
+				Box::new(aimed!(EuclideanHeuristic, map, backwards).with_scale(scale))
+			}
+			HeuristicKind::Octile => Box::new(aimed!(OctileHeuristic, map, backwards).with_weights(straight, diagonal)),
+			HeuristicKind::Chebyshev => Box::new(Scaled::init(aimed!(ChebyshevHeuristic, map, backwards).with_reach(reach), straight)),
+			HeuristicKind::Zero => Box::new(DefaultHeuristic),
+			HeuristicKind::Exact if backwards => weighed!(map, var_move_wt, W, weigher => Box::new(DistanceField::from_initial(map, &weigher))),
+			HeuristicKind::Exact => weighed!(map, var_move_wt, W, weigher => Box::new(DistanceField::init(map, &weigher))),
+			HeuristicKind::Landmark => {
+				let landmarks = select_landmarks(map, var_move_wt);
+				if backwards {
+					Box::new(LandmarkHeuristic::from_initial(map, Arc::new(landmarks)))
+				} else {
+					Box::new(LandmarkHeuristic::init(map, Arc::new(landmarks)))
+				}
+			}
+		}
+	}
+
+	/// Builds the heuristics the method is guided by, see [`HeuristicKind::build_both`],
+	/// with [`DefaultHeuristic`] standing in for the ones it goes without so that nothing's worked out for nothing
+	pub fn build_for(&self, map: &Map<CellType>, var_move_wt: bool, method: Method) -> (Box<dyn Heuristic>, Box<dyn Heuristic>) {
+		if !method.is_informed() {
+			return (Box::new(DefaultHeuristic), Box::new(DefaultHeuristic));
+		}
+		if method == Method::BidirectionalAStar {
+			return self.build_both(map, var_move_wt);
+		}
+		(self.build(map, var_move_wt, false), Box::new(DefaultHeuristic))
+	}
+
+	/// Builds the heuristic both ways, forwards to the targets and backwards from the initial cell
+	pub fn build_both(&self, map: &Map<CellType>, var_move_wt: bool) -> (Box<dyn Heuristic>, Box<dyn Heuristic>) {
+		match self {
+			// The tables are the same both ways, so they're only worked out the once
+			HeuristicKind::Landmark => {
+				let landmarks = select_landmarks(map, var_move_wt);
+				landmark_heuristics(map, Arc::new(landmarks))
+			}
+			_ => (self.build(map, var_move_wt, false), self.build(map, var_move_wt, true))
+		}
+	}
+}
+
+/// Landmarks spread the default way, with the tables weighed by the weigher out of `weighed!`
+pub(crate) fn select_landmarks(map: &Map<CellType>, var_move_wt: bool) -> Landmarks {
+	return weighed!(map, var_move_wt, W, weigher => Landmarks::select(map, &weigher, landmarks::DEFAULT_COUNT, Selection::default()));
+}
+
+/// The ALT heuristic both ways, out of the same tables
+pub(crate) fn landmark_heuristics(map: &Map<CellType>, landmarks: Arc<Landmarks>) -> (Box<dyn Heuristic>, Box<dyn Heuristic>) {
+	return (Box::new(LandmarkHeuristic::init(map, landmarks.clone())), Box::new(LandmarkHeuristic::from_initial(map, landmarks)));
+}
+
+/// Reads the landmark tables out of the file if there is one, or works them out and saves them there for next time
+fn load_landmarks(map: &Map<CellType>, var_move_wt: bool, path: &str) -> Result<Landmarks, Box<dyn Error>> {
+	if Path::new(path).exists() {
+		let landmarks = Landmarks::from_str(&fs::read_to_string(path)?)?;
+		if !weighed!(map, var_move_wt, W, weigher => landmarks.fits(map, &weigher)) {
+			return Err(format!("the landmark tables in {} are for another map, or other move weights", path).into());
+		}
+		return Ok(landmarks);
+	}
+
+	let landmarks = select_landmarks(map, var_move_wt);
+	landmarks.save(path)?;
+	Ok(landmarks)
+}
+
+impl Method {
+	/// Instantiate the search algorithm over the map
+	///
+	/// `var_move_wt` swaps the uniform move weights for the [`CustomMoveWeight`] of the map, the uninformed searches only use it to cost their solution.
+	/// Eight-way maps are weighed with [`EuclideanMoveWeight`] otherwise, so the diagonals cost more than the straight moves.
+	///
+	/// IDA* is given its cost table so that it copes with any map, build it directly for the memory-light version,
+	/// which is what [`runner`] does unless the [`Config`] asks for the table
+	///
+	/// Jump Point Search only holds up when every move costs the same, so `var_move_wt` turns it into plain A*,
+	/// as do terrain and any movement it can't jump with
+	///
+	/// Weighted A* and ARA* start out inflating the heuristic by [`DEFAULT_WEIGHT`]
+	pub fn build<'a>(&self, map: &'a Map<CellType>, var_move_wt: bool) -> Box<dyn Search + 'a> {
+		self.build_with(map, var_move_wt, HeuristicKind::for_map(map, var_move_wt))
+	}
+
+	/// Same as [`Method::build`], but the informed searches are guided by the given heuristic
+	///
+	/// Dijkstra and the uninformed searches go without one either way, so it isn't built for them
+	pub fn build_with<'a>(&self, map: &'a Map<CellType>, var_move_wt: bool, heuristic: HeuristicKind) -> Box<dyn Search + 'a> {
+		let (forwards, backwards) = heuristic.build_for(map, var_move_wt, *self);
+		self.build_guided(map, var_move_wt, forwards, backwards, DEFAULT_WEIGHT)
+	}
+
+	/// Same as [`Method::build`], but guided by heuristics that are already built,
+	/// with `backwards` estimating the cost from the initial cell for bidirectional A*,
+	/// and with the heuristic of weighted A* and the first pass of ARA* inflated by `weight`, which has to be a number from 1 up
+	pub fn build_guided<'a>(&self, map: &'a Map<CellType>, var_move_wt: bool, heu: Box<dyn Heuristic>, backwards: Box<dyn Heuristic>, weight: f64) -> Box<dyn Search + 'a> {
+		weighed!(map, var_move_wt, W, weigher => match self {
+			Method::DepthFirst => Box::new(DepthFirst::with_weigher(map, weigher)),
+			Method::BreadthFirst => Box::new(BreadthFirst::with_weigher(map, weigher)),
+			Method::GreedyBestFirst => graph_search!(map, heu, GBFCursor<W>, weigher = weigher),
+			Method::AStar => graph_search!(map, heu, AStarCursor<W>, cost_table, weigher = weigher),
+			Method::IterativeDeepening => Box::new(IterativeDeepening::with_weigher(map, weigher)),
+			Method::WeightedAStar => graph_search!(map, Inflated::init(heu, weight), AStarCursor<W>, cost_table, weigher = weigher),
+			Method::Dijkstra => graph_search!(map, DefaultHeuristic, DijkstraCursor<W>, cost_table, weigher = weigher),
+			Method::IterativeDeepeningAStar => Box::new(IterativeDeepeningAStar::with_weigher(map, heu, weigher).with_cost_table()),
+			Method::BidirectionalBreadthFirst => Box::new(Bidirectional::breadth_first(map, weigher)),
+			Method::BidirectionalAStar => Box::new(Bidirectional::with_weigher(map, heu, backwards, weigher)),
+			Method::JumpPoint if !var_move_wt && jps::supports(map) => Box::new(JumpPoint::with_weigher(map, heu, weigher)),
+			Method::JumpPoint => graph_search!(map, heu, AStarCursor<W>, cost_table, weigher = weigher),
+			Method::AnytimeRepairingAStar => Box::new(AnytimeRepairingAStar::with_weigher(map, heu, weight, weigher)),
+		})
+	}
+}
+
+impl Method {
+	/// Whether the method is guided by a heuristic at all, only bidirectional A* is guided backwards as well
+	pub fn is_informed(&self) -> bool {
+		!matches!(self, Method::BreadthFirst | Method::DepthFirst | Method::IterativeDeepening | Method::Dijkstra | Method::BidirectionalBreadthFirst)
+	}
+
+	/// Whether the method only promises the cheapest path if its heuristic never overestimates
+	pub fn needs_admissible(&self) -> bool {
+		matches!(self, Method::AStar | Method::IterativeDeepeningAStar | Method::BidirectionalAStar | Method::JumpPoint | Method::AnytimeRepairingAStar)
+	}
+
+	/// Whether the method promises the cheapest path, as long as its heuristic never overestimates if it needs one
+	pub fn finds_cheapest(&self) -> bool {
+		self.needs_admissible() || matches!(self, Method::Dijkstra)
+	}
+}
+
+/// Checks the heuristic asked for in the [`Config`], or the one that goes with the map, against the true costs of the map
+pub fn heuristic_check(map: &Map<CellType>, config: &Config) -> Result<HeuristicCheck, Box<dyn Error>> {
+	let heuristic = match (config.heuristic, &config.landmark_file) {
+		(Some(HeuristicKind::Landmark), Some(path)) => landmark_heuristics(map, Arc::new(load_landmarks(map, config.var_move_wt, path)?)).0,
+		(heuristic, _) => heuristic.unwrap_or_else(|| HeuristicKind::for_map(map, config.var_move_wt)).build(map, config.var_move_wt, false)
+	};
+
+	Ok(weighed!(map, config.var_move_wt, W, weigher => check_heuristic(map, &heuristic, &weigher)))
+}
+
+/// Same as [`Method::build_guided`], except IDA* goes without its cost table unless the [`Config`] asks for it
+fn build_configured<'a>(map: &'a Map<CellType>, config: &Config, method: Method, heu: Box<dyn Heuristic>, backwards: Box<dyn Heuristic>) -> Box<dyn Search + 'a> {
+	if method == Method::IterativeDeepeningAStar && !config.cost_table {
+		return weighed!(map, config.var_move_wt, W, weigher => Box::new(IterativeDeepeningAStar::with_weigher(map, heu, weigher)));
+	}
+	method.build_guided(map, config.var_move_wt, heu, backwards, config.weight())
+}
+
+/// Run the search method named in the [`Config`] over the map, or plan a tour with it if asked to visit all targets
+pub fn runner(map: &Map<CellType>, config: &Config) -> Result<SearchReport, Box<dyn Error>> {
+	let method = Method::from_str(&config.method)?;
+	if !config.weight().is_finite() || config.weight() < 1.0 {
+		return Err(format!("the weight has to be a number from 1 up, got {}", config.weight()).into());
+	}
+
+	if config.visit_all {
+		let landmarks = match (config.heuristic, &config.landmark_file) {
+			(Some(HeuristicKind::Landmark), Some(path)) if method.is_informed() => Some(Arc::new(load_landmarks(map, config.var_move_wt, path)?)),
+			_ => None
+		};
+		return Ok(plan_tour(map, method, config.var_move_wt, config.heuristic, landmarks, config.weight, &config.limits()).report);
+	}
+
+	let heuristic = config.heuristic.unwrap_or_else(|| HeuristicKind::for_map(map, config.var_move_wt));
+	let mut implementation = match (heuristic, &config.landmark_file) {
+		(HeuristicKind::Landmark, Some(path)) if method.is_informed() => {
+			let (forwards, backwards) = landmark_heuristics(map, Arc::new(load_landmarks(map, config.var_move_wt, path)?));
+			build_configured(map, config, method, forwards, backwards)
+		}
+		_ => {
+			let (forwards, backwards) = heuristic.build_for(map, config.var_move_wt, method);
+			build_configured(map, config, method, forwards, backwards)
+		}
+	};
+	implementation.set_limits(config.limits());
+
+	Ok(implementation.search())
+}
@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests;
+
+use crate::map::{Map, CellType};
+use crate::map::parse::{MapParseError, MapParseErrors};
+use crate::runner::{Config, runner};
+use crate::search::Direction;
+use std::error::Error;
+use std::time::Duration;
+
+/// How far a path found with the diagonals weighed as 1.41 can be off the recorded length, where they're √2,
+/// as a fraction of the length
+pub const DIAGONAL_ROUNDING: f64 = std::f64::consts::SQRT_2 / 1.41 - 1.0;
+
+/// A start and goal of the Moving AI Lab benchmarks, along with the length of the shortest path between them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+	/// Line of the scenario file it was read from, counting from 1
+	pub line: usize,
+	/// Scenarios are grouped by how long their paths are
+	pub bucket: usize,
+	/// The `.map` file, as written in the scenario file
+	pub map: String,
+	/// Width and height of the map
+	pub size: (usize, usize),
+	pub start: (usize, usize),
+	pub goal: (usize, usize),
+	/// Length of the shortest path, with diagonals as long as √2
+	pub optimal: f64,
+}
+
+/// Reads a `.scen` file, the `version 1` line and then a line per scenario with its columns split by tabs:
+/// bucket, map, width, height, start x, start y, goal x, goal y and the optimal length
+pub fn read_scenarios(s: &str) -> Result<Vec<Scenario>, MapParseErrors> {
+	let mut scenarios = vec![];
+	let mut errors = vec![];
+
+	for (i, text) in s.lines().enumerate() {
+		let error = |column: usize, token: &str, reason: &str| MapParseError {
+			line: i + 1,
+			column,
+			token: token.to_owned(),
+			reason: reason.to_owned(),
+		};
+		if text.trim().is_empty() || (i == 0 && text.trim().starts_with("version")) {
+			continue;
+		}
+
+		let mut columns = vec![];
+		let mut at = 0;
+		for column in text.split('\t') {
+			columns.push((column.trim(), text[..at].chars().count() + 1));
+			at += column.len() + 1;
+		}
+		if columns.len() != 9 {
+			errors.push(error(1, text.trim(), &format!("a scenario has 9 columns, not {}", columns.len())));
+			continue;
+		}
+
+		let mut numbers = [0; 6];
+		let mut wrong = false;
+		for (n, (token, column)) in numbers.iter_mut().zip(columns[2..8].iter()) {
+			match token.parse::<usize>() {
+				Ok(x) => *n = x,
+				Err(_) => {
+					errors.push(error(*column, token, "not an unsigned number"));
+					wrong = true;
+				}
+			}
+		}
+		let bucket = columns[0].0.parse::<usize>().map_err(|_| error(columns[0].1, columns[0].0, "not an unsigned number"));
+		let optimal = columns[8].0.parse::<f64>().ok().filter(|x| *x >= 0.0).ok_or_else(|| error(columns[8].1, columns[8].0, "not a length"));
+
+		match (bucket, optimal) {
+			(Ok(bucket), Ok(optimal)) if !wrong => scenarios.push(Scenario {
+				line: i + 1,
+				bucket,
+				map: columns[1].0.to_owned(),
+				size: (numbers[0], numbers[1]),
+				start: (numbers[2], numbers[3]),
+				goal: (numbers[4], numbers[5]),
+				optimal,
+			}),
+			(bucket, optimal) => errors.extend(bucket.err().into_iter().chain(optimal.err()))
+		}
+	}
+
+	if errors.is_empty() { Ok(scenarios) } else { Err(MapParseErrors(errors)) }
+}
+
+/// Length of a path with every move as long as the straight line it covers, the way the benchmarks measure it
+pub fn path_length(path: &[Direction]) -> f64 {
+	path.iter()
+		.map(|x| {
+			let (dx, dy) = x.delta();
+			((dx * dx + dy * dy) as f64).sqrt()
+		})
+		.sum()
+}
+
+/// What came of running a search over a [`Scenario`]
+#[derive(Clone, Debug)]
+pub struct Outcome {
+	pub bucket: usize,
+	pub optimal: f64,
+	/// Length of the path found, as measured by [`path_length`]
+	pub length: Option<f64>,
+	pub search_nodes: usize,
+	pub elapsed: Duration,
+}
+
+impl Outcome {
+	/// Whether the path comes out at the recorded length, give or take the [`DIAGONAL_ROUNDING`]
+	pub fn matches(&self) -> bool {
+		self.length.is_some_and(|x| x >= self.optimal - 1e-3 && x <= self.optimal * (1.0 + DIAGONAL_ROUNDING) + 1e-3)
+	}
+}
+
+/// Runs the search method of the [`Config`] from the start to the goal of the scenario, over a map read with [`Map::from_movingai`]
+///
+/// Scenarios for a map of another size, or that start or end in a wall, are refused
+pub fn run_scenario(map: &Map<CellType>, scenario: &Scenario, config: &Config) -> Result<Outcome, Box<dyn Error>> {
+	if scenario.size != (map.cols, map.rows) {
+		return Err(format!("the scenario is for a {} by {} map, not {} by {}", scenario.size.0, scenario.size.1, map.cols, map.rows).into());
+	}
+	for (x, y) in [scenario.start, scenario.goal] {
+		if x >= map.cols || y >= map.rows {
+			return Err(format!("({}, {}) is outside the map", x, y).into());
+		}
+		if let CellType::Wall(_) = map.read_cell((x, y)) {
+			return Err(format!("({}, {}) is a wall", x, y).into());
+		}
+	}
+
+	let report = runner(&map.between(scenario.start, scenario.goal), config)?;
+
+	Ok(Outcome {
+		bucket: scenario.bucket,
+		optimal: scenario.optimal,
+		length: report.solution.as_deref().map(path_length),
+		search_nodes: report.search_nodes,
+		elapsed: report.elapsed,
+	})
+}
+
+/// Totals of the outcomes in a bucket
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bucket {
+	pub bucket: usize,
+	pub runs: usize,
+	pub solved: usize,
+	/// Runs that came out at the recorded length
+	pub matched: usize,
+	pub search_nodes: usize,
+	pub elapsed: Duration,
+}
+
+impl Bucket {
+	pub fn mean_nodes(&self) -> f64 {
+		self.search_nodes as f64 / self.runs.max(1) as f64
+	}
+
+	pub fn mean_elapsed(&self) -> Duration {
+		self.elapsed / self.runs.max(1) as u32
+	}
+}
+
+/// Adds up the outcomes bucket by bucket, in the order of the buckets
+pub fn buckets(outcomes: &[Outcome]) -> Vec<Bucket> {
+	let mut buckets: Vec<Bucket> = vec![];
+
+	for outcome in outcomes {
+		let i = match buckets.binary_search_by_key(&outcome.bucket, |x| x.bucket) {
+			Ok(i) => i,
+			Err(i) => {
+				buckets.insert(i, Bucket { bucket: outcome.bucket, ..Bucket::default() });
+				i
+			}
+		};
+
+		let bucket = &mut buckets[i];
+		bucket.runs += 1;
+		bucket.solved += outcome.length.is_some() as usize;
+		bucket.matched += outcome.matches() as usize;
+		bucket.search_nodes += outcome.search_nodes;
+		bucket.elapsed += outcome.elapsed;
+	}
+
+	return buckets;
+}
@@ -0,0 +1,68 @@
+use crate::map::{Map, CellType};
+use crate::scenario::{read_scenarios, run_scenario, buckets, Scenario};
+use crate::Config;
+
+/// A 6 by 4 map with a wall of trees across the middle, and a gap in it at the bottom
+const MAP: &str = "type octile\nheight 4\nwidth 6\nmap\n..T...\n..T@..\n..T...\n......\n";
+
+fn scenario(start: (usize, usize), goal: (usize, usize), optimal: f64) -> Scenario {
+	Scenario { line: 1, bucket: 0, map: "test.map".to_owned(), size: (6, 4), start, goal, optimal }
+}
+
+#[test]
+fn benchmark_maps_are_read() {
+	let map = Map::from_movingai(MAP).unwrap();
+
+	assert_eq!((map.rows, map.cols, map.initial), (4, 6, (0, 0)));
+	assert!(map.targets.is_empty());
+	assert_eq!(map.read_cell((2, 1)), &CellType::Wall(false));
+	assert_eq!(map.read_cell((3, 1)), &CellType::Wall(false));
+
+	let errors = Map::from_movingai("type octile\nheight 2\nwidth 3\nmap\n..?\n..\n").expect_err("should not parse").0;
+	assert_eq!(errors.iter().map(|x| (x.line, x.column)).collect::<Vec<_>>(), [(5, 3), (6, 1)]);
+	assert!(Map::from_movingai("type hex\nheight 1\nwidth 1\nmap\n.").is_err());
+
+	// A header far bigger than the rows under it
+	let errors = Map::from_movingai("type octile\nheight 4294967296\nwidth 4294967297\nmap\n..\n").expect_err("should not parse").0;
+	assert_eq!(errors.iter().map(|x| (x.line, x.column)).collect::<Vec<_>>(), [(5, 1), (6, 1)]);
+}
+
+#[test]
+fn scenarios_are_read() {
+	let source = "version 1\n3\tmaps/test.map\t6\t4\t0\t0\t5\t0\t9.24264069\n1\ttest.map\t6\t4\t0\t0\tx\t0\t1\n";
+
+	let errors = read_scenarios(source).expect_err("should not parse").0;
+	assert_eq!((errors[0].line, errors[0].column, errors[0].token.as_ref()), (3, 20, "x"));
+
+	let scenarios = read_scenarios(&source[..source.rfind("1\ttest").unwrap()]).unwrap();
+	assert_eq!(scenarios, [Scenario { line: 2, bucket: 3, map: "maps/test.map".to_owned(), ..scenario((0, 0), (5, 0), 9.24264069) }]);
+
+	// Without the version line, and with blank lines in between, the lines are still counted from the top
+	let scenarios = read_scenarios(&source[source.find('\n').unwrap()..source.rfind("1\ttest").unwrap()].replace('\n', "\n\n")).unwrap();
+	assert_eq!(scenarios.iter().map(|x| x.line).collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn cheapest_paths_match_the_recorded_length() {
+	let map = Map::from_movingai(MAP).unwrap();
+	let config = Config { method: "AS".to_owned(), ..Default::default() };
+
+	// Down and around the trees without cutting the corner, 3 diagonals and 5 straight moves
+	let shortest = 5.0 + 3.0 * std::f64::consts::SQRT_2;
+	let outcome = run_scenario(&map, &scenario((0, 0), (5, 0), shortest), &config).unwrap();
+	assert!(outcome.matches(), "{:?}", outcome.length);
+
+	let outcome = run_scenario(&map, &scenario((0, 0), (5, 0), shortest - 1.0), &config).unwrap();
+	assert!(!outcome.matches());
+
+	assert!(run_scenario(&map, &scenario((0, 0), (2, 0), 1.0), &config).is_err());
+	assert!(run_scenario(&map, &Scenario { size: (4, 6), ..scenario((0, 0), (1, 0), 1.0) }, &config).is_err());
+
+	let outcomes = [
+		run_scenario(&map, &Scenario { bucket: 2, ..scenario((0, 0), (1, 0), 1.0) }, &config).unwrap(),
+		outcome,
+		run_scenario(&map, &scenario((0, 0), (0, 0), 0.0), &config).unwrap(),
+	];
+	let totals = buckets(&outcomes);
+	assert_eq!(totals.iter().map(|x| (x.bucket, x.runs, x.solved, x.matched)).collect::<Vec<_>>(), [(0, 2, 2, 1), (2, 1, 1, 1)]);
+}
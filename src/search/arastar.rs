@@ -0,0 +1,302 @@
+use crate::search::{Search, Cursor, MoveWeight, UniformMoveWeight};
+use crate::search::astar::AStarCursor;
+use crate::search::arena::Arena;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::{SearchLimits, Limit};
+use crate::map::{Map, CellType};
+use crate::utils::heuristics::Heuristic;
+use crate::utils::queue::QueueStrategy;
+use crate::utils::queue::sorted::{SortedQueue, Weighted};
+use crate::utils::visited::Visited;
+use crate::report::Tally;
+
+/// How much the inflation is brought down by after each pass when it's not up to the caller
+pub const DEFAULT_STEP: f64 = 0.5;
+
+/// Anytime Repairing A*, weighted A* run over and over with the weight brought down a step each time
+///
+/// The first pass gets to a target quickly with a heavily inflated heuristic, and every pass after that
+/// picks up where the last one left off, only going back over the cells that have been reached more cheaply since they were expanded.
+/// Every solution along the way is handed out as a [`SearchEvent::Improved`], with a bound on how far off the cheapest it is,
+/// until the bound comes down to 1 and the last one is [`SearchEvent::Found`].
+///
+/// Running into a deadline, or any other of the [`SearchLimits`], once there's a solution ends the search with that solution.
+/// The bounds only hold if the heuristic never overestimates.
+pub struct AnytimeRepairingAStar<'a, H, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	heuristic: H,
+	/// Inflation of the heuristic for the first pass
+	initial_weight: f64,
+	/// How much the inflation is brought down by after each pass
+	step: f64,
+	/// Inflation of the heuristic for the current pass
+	weight: f64,
+	queue: SortedQueue<AStarCursor<W>>,
+	arena: Arena,
+	/// Node of the cheapest known route to each cell, keyed by [`Map::index`]
+	best: Vec<Option<usize>>,
+	/// Whether each cell has been expanded in the current pass, keyed by [`Map::index`]
+	closed: Vec<bool>,
+	/// Cells reached more cheaply after they were expanded in the current pass, to be gone over in the next one
+	inconsistent: Vec<(usize, usize)>,
+	/// Node of the cheapest solution so far
+	incumbent: Option<usize>,
+	visited: Visited,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
+}
+
+impl<'a, H, W> Search for AnytimeRepairingAStar<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.give_up(limit);
+				continue;
+			}
+
+			// The pass is over once nothing left on the frontier could lead to a cheaper solution,
+			// or before there's any solution, once the frontier runs dry
+			let cost = self.incumbent.map(|x| self.arena.get(x).cost);
+			match self.queue.peek().filter(|x| cost.is_none_or(|cost| x.weigh() < cost)) {
+				Some(_) => {
+					let cur = self.queue.dequeue().expect("just peeked");
+					self.visit(cur);
+				}
+				None => self.settle()
+			}
+		}
+	}
+}
+
+impl<'a, H> AnytimeRepairingAStar<'a, H> where
+	H: Heuristic {
+	/// The first pass inflates the heuristic by `weight`, which is brought down by [`DEFAULT_STEP`] after each pass
+	pub fn init(map: &'a Map<CellType>, heuristic: H, weight: f64) -> AnytimeRepairingAStar<'a, H> {
+		AnytimeRepairingAStar::with_weigher(map, heuristic, weight, UniformMoveWeight)
+	}
+}
+
+impl<'a, H, W> AnytimeRepairingAStar<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	/// Same as [`AnytimeRepairingAStar::init`], but the moves are costed with the given weigher
+	///
+	/// Weights under 1 are taken as 1, and it panics if the weight isn't a finite number
+	pub fn with_weigher(map: &'a Map<CellType>, heuristic: H, weight: f64, weigher: W) -> AnytimeRepairingAStar<'a, H, W> {
+		assert!(weight.is_finite(), "the heuristic can only be inflated by a finite number, not {}", weight);
+		AnytimeRepairingAStar {
+			map,
+			heuristic,
+			initial_weight: weight.max(1.0),
+			step: DEFAULT_STEP,
+			weight: weight.max(1.0),
+			queue: SortedQueue::init::<AStarCursor<W>>(),
+			arena: Arena::default(),
+			best: vec![None; map.rows * map.cols],
+			closed: vec![false; map.rows * map.cols],
+			inconsistent: vec![],
+			incumbent: None,
+			visited: Visited::init(map),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Bring the inflation down by `step` after each pass instead, smaller steps make for more solutions that are closer together
+	///
+	/// Panics unless the step is over 0, since the inflation would never come down to 1 otherwise
+	pub fn with_step(mut self, step: f64) -> Self {
+		assert!(step > 0.0, "the inflation has to come down by more than 0 after each pass");
+		self.step = step;
+		self
+	}
+
+	/// Clears out the previous run and queues up the initial cell for the first pass
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.weight = self.initial_weight;
+		self.visited.clear();
+		self.arena.clear();
+		while self.queue.dequeue().is_some() {}
+		self.best.iter_mut().for_each(|x| *x = None);
+		self.closed.iter_mut().for_each(|x| *x = false);
+		self.inconsistent.clear();
+		self.incumbent = None;
+
+		let root = self.arena.root(self.map.initial);
+		self.best[self.map.index(self.map.initial)] = Some(root);
+
+		// Nowhere to go if the initial cell is a target
+		if let CellType::Target = self.map.read_cell(self.map.initial) {
+			self.incumbent = Some(root);
+			return;
+		}
+		self.push(self.map.initial, root);
+	}
+
+	/// Queues up a cell that's just been reached more cheaply than before, counting it as generated
+	fn push(&mut self, cell: (usize, usize), node: usize) {
+		self.requeue(cell, node);
+		self.tally.generate(cell, self.queue.len());
+	}
+
+	/// Queues up the cell with the key of the current pass, without counting it, for the cells carried over from the last pass
+	fn requeue(&mut self, cell: (usize, usize), node: usize) {
+		let mut cursor = AStarCursor::<W>::default();
+
+		*cursor.heuristic_weight() = (self.heuristic.estimate(cell) as f64 * self.weight) as usize;
+		*cursor.cursor_mut() = cell;
+		*cursor.node_mut() = node;
+		*cursor.cost_mut() = self.arena.get(node).cost;
+		*cursor.direction_mut() = self.arena.get(node).direction;
+
+		self.queue.queue(cursor);
+	}
+
+	/// Whether the entry is still the cheapest route to a cell that's yet to be expanded this pass
+	fn is_current(&self, cur: &AStarCursor<W>) -> bool {
+		let i = self.map.index(*cur.cursor());
+		self.best[i] == Some(cur.node()) && !self.closed[i]
+	}
+
+	/// Expands a node that's just been taken off the frontier
+	fn visit(&mut self, cur: AStarCursor<W>) {
+		self.tally.search_nodes += 1;
+
+		if !self.is_current(&cur) {
+			self.tally.prune(*cur.cursor());
+			return;
+		}
+
+		let cell = *cur.cursor();
+		self.closed[self.map.index(cell)] = true;
+		self.visited.insert(cell);
+		self.tally.expand(cell);
+
+		for (dir, pos) in self.map.adjacents(cell) {
+			if let CellType::Wall(_) = self.map.read_cell(pos) {
+				continue;
+			}
+
+			let weight = self.map.move_cost(&self.weigher, dir, pos);
+			let parent = self.arena.get(cur.node());
+			let (depth, cost) = (parent.depth + 1, parent.cost + weight);
+
+			if !self.tally.within(depth, cost) {
+				continue;
+			}
+
+			let i = self.map.index(pos);
+			if self.best[i].is_some_and(|x| self.arena.get(x).cost <= cost) {
+				self.tally.prune(pos);
+				continue;
+			}
+
+			let node = self.arena.branch(cur.node(), dir, pos, weight);
+			self.best[i] = Some(node);
+
+			// Targets are never expanded, reaching one is enough to improve on the solution
+			if let CellType::Target = self.map.read_cell(pos) {
+				if self.incumbent.is_none_or(|x| self.arena.get(x).cost > cost) {
+					self.incumbent = Some(node);
+				}
+				continue;
+			}
+
+			if self.closed[i] {
+				self.inconsistent.push(pos);
+			} else {
+				self.push(pos, node);
+			}
+		}
+	}
+
+	/// Wraps up a pass, handing out its solution and setting up the next pass with less inflation
+	fn settle(&mut self) {
+		let incumbent = match self.incumbent {
+			Some(x) => x,
+			// Nothing's left on the frontier, and every cell that's been reached more cheaply can't lead anywhere new
+			None => {
+				self.tally.exhausted(&self.visited);
+				return;
+			}
+		};
+
+		// What's left on the frontier, and the cells to go over again, make up the rest of the search
+		let mut frontier = std::mem::take(&mut self.inconsistent);
+		while let Some(cur) = self.queue.dequeue() {
+			if self.is_current(&cur) {
+				frontier.push(*cur.cursor());
+			}
+		}
+		frontier.sort_unstable();
+		frontier.dedup();
+
+		// None of them can lead to a target cheaper than their cost plus the estimate
+		let cost = self.arena.get(incumbent).cost;
+		let lowest = frontier.iter()
+			.map(|x| self.arena.get(self.best[self.map.index(*x)].expect("reached")).cost + self.heuristic.estimate(*x))
+			.min();
+		let bound = match lowest {
+			Some(x) if x < cost => self.weight.min(cost as f64 / x as f64),
+			_ => 1.0
+		};
+		self.tally.suboptimality = Some(bound);
+
+		let path = self.arena.path(incumbent);
+		let target = self.arena.get(incumbent).cursor;
+		if bound <= 1.0 {
+			self.tally.found(cost, target, path, &self.visited);
+			return;
+		}
+		self.tally.improved(cost, target, path, &self.visited);
+
+		// Inflating past the bound doesn't buy anything, which brings a huge first weight straight down
+		self.weight = (self.weight - self.step).min(bound).max(1.0);
+		self.closed.iter_mut().for_each(|x| *x = false);
+		for cell in frontier {
+			let node = self.best[self.map.index(cell)].expect("reached");
+			self.requeue(cell, node);
+		}
+	}
+
+	/// Ends the search on running into the `limit`, with the solution so far if there is one
+	fn give_up(&mut self, limit: Limit) {
+		match self.incumbent {
+			Some(x) => {
+				let node = self.arena.get(x);
+				let (cost, target) = (node.cost, node.cursor);
+				self.tally.settled(limit, cost, target, self.arena.path(x), &self.visited);
+			}
+			None => self.tally.cut_off(&self.visited, limit)
+		}
+	}
+}
@@ -0,0 +1,91 @@
+use crate::search::Direction;
+
+/// A node in the search tree, which only remembers the node it was reached from
+#[derive(Clone, Copy, Debug)]
+pub struct Node {
+	/// Coordinates of the cell this node is on
+	pub cursor: (usize, usize),
+	/// Index of the node this node was reached from, [`None`] for the root
+	pub parent: Option<usize>,
+	/// Direction taken from the parent to reach this node
+	pub direction: Option<Direction>,
+	/// Travelled weight from the root to this node
+	pub cost: usize,
+	/// Number of moves from the root to this node
+	pub depth: usize,
+}
+
+/// Backing storage of a search tree, where children point at their parents by index
+///
+/// This spares the searches from copying the entire path into every child,
+/// the path is only pieced back together once a target has been found
+#[derive(Default)]
+pub struct Arena {
+	nodes: Vec<Node>,
+}
+
+impl Arena {
+	/// Drops the whole tree
+	pub fn clear(&mut self) {
+		self.nodes.clear();
+	}
+
+	/// Adds a node with no parent, returning its index
+	pub fn root(&mut self, cursor: (usize, usize)) -> usize {
+		self.nodes.push(Node { cursor, parent: None, direction: None, cost: 0, depth: 0 });
+		self.nodes.len() - 1
+	}
+
+	/// Adds a node reached from `parent` by moving in `direction` for `weight`, returning its index
+	pub fn branch(&mut self, parent: usize, direction: Direction, cursor: (usize, usize), weight: usize) -> usize {
+		let cost = self.nodes[parent].cost + weight;
+		let depth = self.nodes[parent].depth + 1;
+		self.nodes.push(Node { cursor, parent: Some(parent), direction: Some(direction), cost, depth });
+		self.nodes.len() - 1
+	}
+
+	/// Same as [`Arena::branch`], but the node is reached by making the same move `moves` times over, for `weight` in total
+	pub fn leap(&mut self, parent: usize, direction: Direction, cursor: (usize, usize), moves: usize, weight: usize) -> usize {
+		let cost = self.nodes[parent].cost + weight;
+		let depth = self.nodes[parent].depth + moves;
+		self.nodes.push(Node { cursor, parent: Some(parent), direction: Some(direction), cost, depth });
+		self.nodes.len() - 1
+	}
+
+	pub fn get(&self, index: usize) -> &Node {
+		&self.nodes[index]
+	}
+
+	/// Iterates from the node up towards the root, starting with the node itself
+	pub fn ancestry(&self, index: usize) -> impl Iterator<Item=&Node> + '_ {
+		let mut next = Some(index);
+		std::iter::from_fn(move || {
+			let node = &self.nodes[next?];
+			next = node.parent;
+			Some(node)
+		})
+	}
+
+	/// Pieces together the directions taken from the root to reach the node
+	///
+	/// Nodes that were reached by a [`Arena::leap`] have their moves filled back in one at a time
+	pub fn path(&self, index: usize) -> Vec<Direction> {
+		let ancestry: Vec<_> = self.ancestry(index).collect();
+
+		let mut path = vec![];
+		for pair in ancestry.windows(2).rev() {
+			let (node, parent) = (pair[0], pair[1]);
+			let direction = node.direction.expect("only the root has no direction");
+			path.extend(std::iter::repeat_n(direction, node.depth - parent.depth));
+		}
+		return path;
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+}
@@ -1,24 +1,27 @@
 use crate::search::{Direction, Cursor, MoveWeight};
 use crate::utils::queue::sorted::Weighted;
+use std::marker::PhantomData;
 
 #[derive(Default, Debug)]
 pub struct AStarCursor<W> {
 	heuristic_weight: usize,
-	path: Vec<Direction>,
+	cost: usize,
+	node: usize,
 	cursor: (usize, usize),
-	weigher: W,
+	direction: Option<Direction>,
+	_weigher: PhantomData<W>,
 }
 
 impl<W> Cursor for AStarCursor<W> where
 	W: MoveWeight {
 	type DirectionWeigher = W;
 
-	fn path(&self) -> &Vec<Direction> {
-		&self.path
+	fn node(&self) -> usize {
+		self.node
 	}
 
-	fn path_mut(&mut self) -> &mut Vec<Direction> {
-		&mut self.path
+	fn node_mut(&mut self) -> &mut usize {
+		&mut self.node
 	}
 
 	fn cursor(&self) -> &(usize, usize) {
@@ -29,18 +32,20 @@ impl<W> Cursor for AStarCursor<W> where
 		&mut self.cursor
 	}
 
-	fn into_path(self) -> Vec<Direction> {
-		self.path
+	fn cost_mut(&mut self) -> &mut usize {
+		&mut self.cost
+	}
+
+	fn direction_mut(&mut self) -> &mut Option<Direction> {
+		&mut self.direction
 	}
 }
 
 impl<W> Weighted for AStarCursor<W> where
 	W: MoveWeight {
-	/// AStar weight = travelled weight + heuristic weight
+	/// AStar weight = travelled weight + heuristic weight, which stops at the biggest weight rather than overflowing
 	fn weigh(&self) -> usize {
-		self.heuristic_weight + self.path.iter()
-			.map(|d| self.weigher.weigh(d))
-			.fold(0, |fold, add| fold + add)
+		self.heuristic_weight.saturating_add(self.cost)
 	}
 
 	fn heuristic_weight(&mut self) -> &mut usize {
@@ -48,6 +53,6 @@ impl<W> Weighted for AStarCursor<W> where
 	}
 
 	fn direction(&self) -> Option<Direction> {
-		self.path.last().map(|x| *x)
+		self.direction
 	}
 }
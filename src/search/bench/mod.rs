@@ -1,112 +1,215 @@
 use test::Bencher;
 use crate::Map;
-use crate::utils::filter;
-use crate::search::graph_search::GraphSearch;
-use crate::utils::queue::sorted::SortedQueue;
-use crate::{AStarCursor, DijkstraCursor, GBFCursor, ManhattanHeuristic, UniformMoveWeight, WeightedASCursor};
+use crate::UniformMoveWeight;
+use crate::search::astar::AStarCursor;
+use crate::search::dijkstra::DijkstraCursor;
+use crate::search::gbfs::GBFCursor;
+use crate::search::wastar::{Inflated, DEFAULT_WEIGHT};
+use crate::utils::heuristics::{ManhattanHeuristic, LandmarkHeuristic};
+use crate::utils::landmarks::{self, Landmarks, Selection};
 use crate::search::Search;
-use std::mem;
 use crate::search::bfs::BreadthFirst;
 use crate::search::dfs::DepthFirst;
 use crate::search::iddfs::IterativeDeepening;
+use crate::search::idastar::IterativeDeepeningAStar;
+use crate::search::bidirectional::Bidirectional;
+use crate::search::jps::JumpPoint;
+use std::str::FromStr;
+use std::sync::Arc;
 
-const STATIC_MAP: &'static str = include_str!("map.txt");
+const STATIC_MAP: &str = include_str!("map.txt");
+/// Wall-less map with the target in the far corner, so the paths get long
+const OPEN_FIELD: &str = "[250, 250]\n(0, 0)\n(249, 249)\n";
 
 #[bench]
 fn a_star(b: &mut Bencher) {
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = graph_search!(&mut map, ManhattanHeuristic::init(&map), AStarCursor<UniformMoveWeight>);
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), AStarCursor<UniformMoveWeight>);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn jump_point(b: &mut Bencher) {
+	b.iter(|| {
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = JumpPoint::init(&map, ManhattanHeuristic::init(&map));
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
 #[bench]
 fn breadth_first(b: &mut Bencher) {
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = BreadthFirst::init(&mut map);
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = BreadthFirst::init(&map);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn depth_first(b: &mut Bencher) {
+	b.iter(|| {
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = DepthFirst::init(&map);
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
 #[bench]
-fn depth_first(b: &mut Bencher) {
+fn iddfs(b: &mut Bencher) {
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = DepthFirst::init(&mut map);
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = IterativeDeepening::init(&map);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn ida_star_cost_table(b: &mut Bencher) {
+	b.iter(|| {
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = IterativeDeepeningAStar::init(&map, ManhattanHeuristic::init(&map)).with_cost_table();
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
 #[bench]
-fn iddfs(b: &mut Bencher) {
+fn dijkstra(b: &mut Bencher) {
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = IterativeDeepening::init(&mut map);
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), DijkstraCursor<UniformMoveWeight>);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn greedy_best_first(b: &mut Bencher) {
+	b.iter(|| {
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), GBFCursor<UniformMoveWeight>);
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
 #[bench]
-fn dijkstra(b: &mut Bencher) {
+fn weighted_astar(b: &mut Bencher) {
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = graph_search!(&mut map, ManhattanHeuristic::init(&map), DijkstraCursor<UniformMoveWeight>);
+		let map = Map::random_maze(50, 50, 2);
+		let mut search = graph_search!(&map, Inflated::init(ManhattanHeuristic::init(&map), DEFAULT_WEIGHT), AStarCursor<UniformMoveWeight>);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
 
-		map.clear_visits();
+#[bench]
+fn a_star_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	b.iter(|| {
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), AStarCursor<UniformMoveWeight>);
+
+		let _ = search.search();
 	})
 }
 
 #[bench]
-fn greedy_best_first(b: &mut Bencher) {
+fn jump_point_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	b.iter(|| {
+		let mut search = JumpPoint::init(&map, ManhattanHeuristic::init(&map));
+
+		let _ = search.search();
+	})
+}
+
+#[bench]
+fn a_star_landmarks_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	// Worked out once up front, as they would be when read back from a file
+	let landmarks = Arc::new(Landmarks::select(&map, &UniformMoveWeight, landmarks::DEFAULT_COUNT, Selection::Farthest));
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = graph_search!(&mut map, ManhattanHeuristic::init(&map), GBFCursor<UniformMoveWeight>);
+		let mut search = graph_search!(&map, LandmarkHeuristic::init(&map, landmarks.clone()), AStarCursor<UniformMoveWeight>);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn breadth_first_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	b.iter(|| {
+		let mut search = BreadthFirst::init(&map);
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
 #[bench]
-fn weighted_astar(b: &mut Bencher) {
+fn dijkstra_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	b.iter(|| {
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), DijkstraCursor<UniformMoveWeight>);
+
+		let _ = search.search();
+	})
+}
+
+#[bench]
+fn a_star_open_field(b: &mut Bencher) {
+	let map = Map::from_str(OPEN_FIELD).unwrap();
+	b.iter(|| {
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), AStarCursor<UniformMoveWeight>);
+
+		let _ = search.search();
+	})
+}
+
+#[bench]
+fn jump_point_open_field(b: &mut Bencher) {
+	let map = Map::from_str(OPEN_FIELD).unwrap();
+	b.iter(|| {
+		let mut search = JumpPoint::init(&map, ManhattanHeuristic::init(&map));
+
+		let _ = search.search();
+	})
+}
+
+#[bench]
+fn dijkstra_open_field(b: &mut Bencher) {
+	let map = Map::from_str(OPEN_FIELD).unwrap();
 	b.iter(|| {
-		let mut map = Map::random_maze(50, 50, 2);
-		let mut search = graph_search!(&mut map, ManhattanHeuristic::init(&map), WeightedASCursor<UniformMoveWeight>);
+		let mut search = graph_search!(&map, ManhattanHeuristic::init(&map), DijkstraCursor<UniformMoveWeight>);
 
 		let _ = search.search();
+	})
+}
 
-		mem::drop(search);
+#[bench]
+fn bidirectional_a_star_open_field(b: &mut Bencher) {
+	let map = Map::from_str(OPEN_FIELD).unwrap();
+	b.iter(|| {
+		let mut search = Bidirectional::init(&map, ManhattanHeuristic::init(&map), ManhattanHeuristic::from_initial(&map));
 
-		map.clear_visits();
+		let _ = search.search();
 	})
 }
 
+#[bench]
+fn bidirectional_breadth_first_static(b: &mut Bencher) {
+	let map = Map::from_str(STATIC_MAP).unwrap();
+	b.iter(|| {
+		let mut search = Bidirectional::breadth_first(&map, UniformMoveWeight);
+
+		let _ = search.search();
+	})
+}
@@ -1,80 +1,157 @@
-use crate::search::{Search, Direction};
+use crate::search::{Search, MoveWeight, UniformMoveWeight};
+use crate::search::arena::Arena;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
 use crate::map::{Map, CellType};
 use crate::utils::queue::fifo::FIFOQueue;
 use crate::utils::queue::QueueStrategy;
-use crate::SearchReport;
+use crate::utils::visited::Visited;
+use crate::report::Tally;
 
-pub struct BreadthFirst<'a> {
-	map: &'a mut Map<CellType>
+/// It's a tree based search with the parents enumerated by the cursor
+/// and the state repetitions tracked in the closed set
+pub struct BreadthFirst<'a, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	/// The FIFO queue for sending [`BFSCursor`]s around; the "frontier"
+	queue: FIFOQueue<BFSCursor>,
+	visited: Visited,
+	arena: Arena,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
 }
 
-impl<'a> Search for BreadthFirst<'a> {
-	/// It's a tree based search with the parents enumerated by the cursor
-	/// and the state repetitions written onto the map directly
-	fn search(&mut self) -> SearchReport {
-		// This creates a FIFO queue for sending [`Cursor`]s around; the "frontier"
-		let mut queue = FIFOQueue::init();
-
-		// Kickstart the search
-		queue.queue(BFSCursor { path: vec![], cursor: self.map.initial });
-
-		let mut i = 0;
-
-		while let Some(cur) = queue.dequeue() {
-			i += 1;
-			// Mark current cell
-			match self.map.read_cell_mut(cur.cursor) {
-				// If it's a cell that's not a path or target
-				CellType::Initial(ref mut visited) |
-				CellType::Blank(ref mut visited) |
-				CellType::Wall(ref mut visited) => if *visited {
-					continue; // This removes repeated states
-					// Identical states on alternative branches are also pruned
-					// because it's a lot more work to do the checking, and waste of effort anyway
-					// since this is uninformed search
-				} else {
-					*visited = true;
-				}
-				CellType::Target => {
-					return SearchReport { search_nodes: i, solution: Some(cur.path) };
-				}
-				#[cfg(feature = "eyecandy")]
-				_ => continue
-			}
+impl<'a, W> Search for BreadthFirst<'a, W> where
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
 
-			self.expose_next_layer(cur, &mut queue);
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
 		}
 
-		return SearchReport { search_nodes: i, solution: None };
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.visited, limit);
+				continue;
+			}
+
+			match self.queue.dequeue() {
+				Some(cur) => self.visit(cur),
+				None => self.tally.exhausted(&self.visited)
+			}
+		}
 	}
 }
 
 /// Basic cursor since BFS does not use the stack to store its state
 #[derive(Debug)]
 pub struct BFSCursor {
-	pub path: Vec<Direction>,
+	/// Index of the node in the search's [`Arena`]
+	pub node: usize,
 	pub cursor: (usize, usize),
 }
 
 impl<'a> BreadthFirst<'a> {
-	pub fn init(map: &mut Map<CellType>) -> BreadthFirst {
-		BreadthFirst { map }
+	pub fn init(map: &'a Map<CellType>) -> BreadthFirst<'a> {
+		BreadthFirst::with_weigher(map, UniformMoveWeight)
+	}
+}
+
+impl<'a, W> BreadthFirst<'a, W> where
+	W: MoveWeight {
+	/// Same as [`BreadthFirst::init`], but the solution is costed with the given weigher
+	pub fn with_weigher(map: &'a Map<CellType>, weigher: W) -> BreadthFirst<'a, W> {
+		BreadthFirst {
+			map,
+			queue: FIFOQueue::init(),
+			visited: Visited::init(map),
+			arena: Arena::default(),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Clears out the previous run and queues up the initial cell
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.visited.clear();
+		self.arena.clear();
+		self.queue = FIFOQueue::init();
+
+		self.queue.queue(BFSCursor { node: self.arena.root(self.map.initial), cursor: self.map.initial });
+		self.tally.generate(self.map.initial, self.queue.len());
+	}
+
+	/// Deals with a node that's just been taken off the frontier
+	fn visit(&mut self, cur: BFSCursor) {
+		self.tally.search_nodes += 1;
+		// Mark current cell
+		match self.map.read_cell(cur.cursor) {
+			// If it's a cell that's not a path or target
+			CellType::Initial(_) |
+			CellType::Blank(_) |
+			CellType::Wall(_) => if !self.visited.insert(cur.cursor) {
+				self.tally.prune(cur.cursor);
+				return; // This removes repeated states
+				// Identical states on alternative branches are also pruned
+				// because it's a lot more work to do the checking, and waste of effort anyway
+				// since this is uninformed search
+			}
+			CellType::Target => {
+				let path = self.arena.path(cur.node);
+				self.tally.found(self.map.path_cost(&self.weigher, &path), cur.cursor, path, &self.visited);
+				return;
+			}
+			#[cfg(feature = "eyecandy")]
+			_ => return
+		}
+
+		self.expose_next_layer(cur);
 	}
 
-	/// Takes the sender of the FIFO queue and attach all children to it
+	/// Attach all children to the FIFO queue
 	/// But only if it's a blank cell, otherwise it's ignored
-	fn expose_next_layer(&mut self, cur: BFSCursor, queue: &mut FIFOQueue<BFSCursor>) {
+	fn expose_next_layer(&mut self, cur: BFSCursor) {
 		match self.map.read_cell(cur.cursor) {
 			CellType::Initial(_) | CellType::Blank(_) => { // Unvisited blank, add neighbours to queue
+				self.tally.expand(cur.cursor);
+
 				for (dir, pos) in self.map.adjacents(cur.cursor) {
-					let mut path = cur.path.clone();
-					path.push(dir);
+					let weight = self.map.move_cost(&self.weigher, dir, pos);
+					let parent = self.arena.get(cur.node);
+
+					if !self.tally.within(parent.depth + 1, parent.cost + weight) {
+						continue;
+					}
 
-					queue.queue(BFSCursor { path, cursor: pos });
+					let node = self.arena.branch(cur.node, dir, pos, weight);
+
+					self.queue.queue(BFSCursor { node, cursor: pos });
+					self.tally.generate(pos, self.queue.len());
 				}
 			}
 			// Walls, Paths etc. are just left alone
 			_ => {}
 		}
 	}
-}
\ No newline at end of file
+}
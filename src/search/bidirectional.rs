@@ -0,0 +1,334 @@
+use crate::search::{Search, Cursor, MoveWeight, UniformMoveWeight};
+use crate::search::arena::{Arena, Node};
+use crate::search::astar::AStarCursor;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
+use crate::map::{Map, CellType};
+use crate::utils::heuristics::{Heuristic, DefaultHeuristic};
+use crate::utils::queue::QueueStrategy;
+use crate::utils::queue::sorted::{SortedQueue, Weighted};
+use crate::utils::visited::Visited;
+use crate::report::Tally;
+
+const FORWARD: usize = 0;
+const BACKWARD: usize = 1;
+
+/// Searches forwards from the initial cell and backwards from all the targets at once, until the two meet in the middle
+///
+/// Each half keeps the cheapest known cost of reaching each cell, and every cell both halves have reached is a meeting point.
+/// The search stops once neither frontier can lead to a cheaper meeting than the best one so far.
+/// The half with the smaller frontier is the one that's advanced, so that neither runs off on its own.
+///
+/// The backward half walks the moves in reverse, but weighs them the way they're made going forwards,
+/// so asymmetric move weights such as [`CustomMoveWeight`](crate::search::CustomMoveWeight) are accounted for.
+/// The [`SearchLimits`] on depth and cost apply to each half, as well as to the paths they piece together.
+pub struct Bidirectional<'a, H, B, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	/// Estimates the cost to the nearest target, for the forward half
+	heuristic: H,
+	/// Estimates the cost from the initial cell, for the backward half
+	back_heuristic: B,
+	halves: [Half<W>; 2],
+	/// Cost of the cheapest path through a cell both halves have reached, and that cell
+	meeting: Option<(usize, (usize, usize))>,
+	/// Count every move as 1 while searching, which makes both halves breadth-first
+	layered: bool,
+	visited: Visited,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
+}
+
+/// One direction of a [`Bidirectional`] search
+struct Half<W> {
+	arena: Arena,
+	queue: SortedQueue<AStarCursor<W>>,
+	/// Cheapest known cost to each cell, or the fewest moves going breadth-first, keyed by [`Map::index`]
+	best_costs: Vec<usize>,
+	/// The node in the arena that has the cheapest known cost, keyed by [`Map::index`]
+	best_nodes: Vec<usize>,
+}
+
+impl<W> Half<W> where
+	W: MoveWeight {
+	fn init(map: &Map<CellType>) -> Half<W> {
+		Half {
+			arena: Arena::default(),
+			queue: SortedQueue::init(),
+			best_costs: vec![usize::MAX; map.rows * map.cols],
+			best_nodes: vec![usize::MAX; map.rows * map.cols],
+		}
+	}
+
+	fn clear(&mut self) {
+		self.arena.clear();
+		self.queue = SortedQueue::init();
+		self.best_costs.iter_mut().for_each(|x| *x = usize::MAX);
+		self.best_nodes.iter_mut().for_each(|x| *x = usize::MAX);
+	}
+
+	/// Puts a node on the frontier as the cheapest way of reaching its cell, for `cost`
+	fn push(&mut self, index: usize, node: usize, cost: usize, estimate: usize) {
+		let cell = self.arena.get(node);
+
+		self.best_costs[index] = cost;
+		self.best_nodes[index] = node;
+
+		let mut cursor = AStarCursor::default();
+		*cursor.heuristic_weight() = estimate;
+		*cursor.cursor_mut() = cell.cursor;
+		*cursor.node_mut() = node;
+		*cursor.cost_mut() = cost;
+		*cursor.direction_mut() = cell.direction;
+
+		self.queue.queue(cursor);
+	}
+
+	/// Weight of the lightest node on the frontier
+	fn lightest(&self) -> Option<usize> {
+		self.queue.peek().map(|x| x.weigh())
+	}
+}
+
+impl<'a, H, B, W> Search for Bidirectional<'a, H, B, W> where
+	H: Heuristic,
+	B: Heuristic,
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.visited, limit);
+				continue;
+			}
+
+			if self.settled() {
+				self.finish();
+				continue;
+			}
+
+			let side = if self.halves[BACKWARD].queue.len() < self.halves[FORWARD].queue.len() {
+				BACKWARD
+			} else {
+				FORWARD
+			};
+			let cur = self.halves[side].queue.dequeue().expect("checked to be non-empty by settled");
+			self.visit(side, cur);
+		}
+	}
+}
+
+impl<'a, W> Bidirectional<'a, DefaultHeuristic, DefaultHeuristic, W> where
+	W: MoveWeight {
+	/// Both halves go breadth-first, the solution is costed with the given weigher
+	pub fn breadth_first(map: &'a Map<CellType>, weigher: W) -> Bidirectional<'a, DefaultHeuristic, DefaultHeuristic, W> {
+		let mut search = Bidirectional::with_weigher(map, DefaultHeuristic, DefaultHeuristic, weigher);
+		search.layered = true;
+		search
+	}
+}
+
+impl<'a, H, B> Bidirectional<'a, H, B> where
+	H: Heuristic,
+	B: Heuristic {
+	/// Both halves go by A*, `heuristic` estimates the cost to the targets and `back_heuristic` the cost from the initial cell
+	pub fn init(map: &'a Map<CellType>, heuristic: H, back_heuristic: B) -> Bidirectional<'a, H, B> {
+		Bidirectional::with_weigher(map, heuristic, back_heuristic, UniformMoveWeight)
+	}
+}
+
+impl<'a, H, B, W> Bidirectional<'a, H, B, W> where
+	H: Heuristic,
+	B: Heuristic,
+	W: MoveWeight {
+	/// Same as [`Bidirectional::init`], but the moves are costed with the given weigher
+	pub fn with_weigher(map: &'a Map<CellType>, heuristic: H, back_heuristic: B, weigher: W) -> Bidirectional<'a, H, B, W> {
+		Bidirectional {
+			map,
+			heuristic,
+			back_heuristic,
+			halves: [Half::init(map), Half::init(map)],
+			meeting: None,
+			layered: false,
+			visited: Visited::init(map),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Clears out the previous run, then seeds the forward half with the initial cell and the backward half with the targets
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.visited.clear();
+		self.halves.iter_mut().for_each(|x| x.clear());
+		self.meeting = None;
+
+		let initial = self.map.initial;
+		let node = self.halves[FORWARD].arena.root(initial);
+		self.halves[FORWARD].push(self.map.index(initial), node, 0, self.heuristic.estimate(initial));
+		self.tally.generate(initial, 1);
+
+		for target in &self.map.targets {
+			let index = self.map.index(*target);
+			if self.halves[BACKWARD].best_costs[index] == 0 {
+				continue;
+			}
+
+			let node = self.halves[BACKWARD].arena.root(*target);
+			self.halves[BACKWARD].push(index, node, 0, self.back_heuristic.estimate(*target));
+			self.tally.generate(*target, self.frontier());
+
+			// Already there, the halves meet before they've made a move
+			if *target == initial {
+				self.meeting = Some((0, initial));
+			}
+		}
+	}
+
+	/// What the halves go by, the number of moves going breadth-first and the cost otherwise
+	fn rank(&self, node: &Node) -> usize {
+		if self.layered { node.depth } else { node.cost }
+	}
+
+	fn frontier(&self) -> usize {
+		self.halves[FORWARD].queue.len() + self.halves[BACKWARD].queue.len()
+	}
+
+	/// Whether there's no cheaper meeting to be found
+	///
+	/// Breadth-first, no meeting can be cheaper than the lightest nodes of both frontiers put together.
+	/// With heuristics, no meeting can be cheaper than the estimate of the lightest node on either frontier.
+	fn settled(&self) -> bool {
+		let (forward, backward) = match (self.halves[FORWARD].lightest(), self.halves[BACKWARD].lightest()) {
+			(Some(forward), Some(backward)) => (forward, backward),
+			// One half has run out, so it's been everywhere it can reach
+			_ => return true
+		};
+
+		match self.meeting {
+			Some((cost, _)) if self.layered => forward + backward >= cost,
+			Some((cost, _)) => forward.max(backward) >= cost,
+			None => false
+		}
+	}
+
+	/// Ends the search with the cheapest meeting, if the halves ever met
+	fn finish(&mut self) {
+		let meeting = match self.meeting {
+			Some((_, meeting)) => meeting,
+			None => {
+				self.tally.exhausted(&self.visited);
+				return;
+			}
+		};
+		let index = self.map.index(meeting);
+
+		let mut path = self.halves[FORWARD].arena.path(self.halves[FORWARD].best_nodes[index]);
+		// The backward nodes hold the direction towards their parents, so their ancestry reads forwards
+		let back = &self.halves[BACKWARD].arena;
+		let ancestry: Vec<_> = back.ancestry(self.halves[BACKWARD].best_nodes[index]).collect();
+		path.extend(ancestry.iter().filter_map(|x| x.direction));
+		let target = ancestry.last().expect("ancestry has at least the node itself").cursor;
+
+		self.tally.meeting = Some(meeting);
+		self.tally.found(self.map.path_cost(&self.weigher, &path), target, path, &self.visited);
+	}
+
+	/// Deals with a node that's just been taken off the frontier of the `side`
+	fn visit(&mut self, side: usize, cur: AStarCursor<W>) {
+		self.tally.search_nodes += 1;
+
+		let pos = *cur.cursor();
+		let index = self.map.index(pos);
+		let cost = self.rank(self.halves[side].arena.get(cur.node()));
+
+		// Stale entry, the cell has since been reached more cheaply
+		if cost > self.halves[side].best_costs[index] {
+			self.tally.prune(pos);
+			return;
+		}
+		self.visited.insert(pos);
+
+		// There's no point going past the end of a half, that's only ever costlier
+		match (side, self.map.read_cell(pos)) {
+			(FORWARD, CellType::Target) | (BACKWARD, CellType::Initial(_)) => return,
+			_ => {}
+		}
+		self.tally.expand(pos);
+
+		for (dir, next) in self.map.adjacents(pos) {
+			if let CellType::Wall(_) = self.map.read_cell(next) {
+				continue;
+			}
+
+			// Going backwards, the move that's made is from the neighbour onto this cell
+			let (forward_dir, onto) = if side == FORWARD { (dir, next) } else { (dir.opposite(), pos) };
+			let weight = self.map.move_cost(&self.weigher, forward_dir, onto);
+
+			let parent = self.halves[side].arena.get(cur.node());
+			if !self.tally.within(parent.depth + 1, parent.cost + weight) {
+				continue;
+			}
+			let cost = if self.layered { parent.depth + 1 } else { parent.cost + weight };
+
+			let next_index = self.map.index(next);
+			if cost >= self.halves[side].best_costs[next_index] {
+				self.tally.prune(next);
+				continue;
+			}
+
+			let estimate = if side == FORWARD {
+				self.heuristic.estimate(next)
+			} else {
+				self.back_heuristic.estimate(next)
+			};
+			let node = self.halves[side].arena.branch(cur.node(), forward_dir, next, weight);
+			self.halves[side].push(next_index, node, cost, estimate);
+			self.tally.generate(next, self.frontier());
+
+			// The other half has been here too
+			if self.halves[1 - side].best_costs[next_index] != usize::MAX {
+				self.meet(next, next_index);
+			}
+		}
+	}
+
+	/// Notes down the path through the cell where both halves have been, if it's the cheapest one yet
+	fn meet(&mut self, cell: (usize, usize), index: usize) {
+		let forward = self.halves[FORWARD].arena.get(self.halves[FORWARD].best_nodes[index]);
+		let backward = self.halves[BACKWARD].arena.get(self.halves[BACKWARD].best_nodes[index]);
+
+		if !self.tally.within(forward.depth + backward.depth, forward.cost + backward.cost) {
+			return;
+		}
+		let cost = self.rank(forward) + self.rank(backward);
+
+		if self.meeting.is_none_or(|(best, _)| cost < best) {
+			self.meeting = Some((cost, cell));
+		}
+	}
+}
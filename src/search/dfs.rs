@@ -1,71 +1,165 @@
-use crate::search::{Search, Direction};
+use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight};
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
 use crate::map::{CellType, Map};
-use crate::SearchReport;
+use crate::utils::visited::Visited;
+use crate::report::Tally;
 
-pub enum SearchResult {
-	Hit(usize, Vec<Direction>),
-	Miss(usize),
+/// A cell on the depth-first stack, along with how far the search has got through its neighbours
+pub(crate) struct Frame {
+	pub cursor: (usize, usize),
+	/// Direction taken from the cell below this one on the stack
+	pub direction: Option<Direction>,
+	/// Travelled weight from the initial cell to this one
+	pub cost: usize,
+	neighbours: Vec<(Direction, (usize, usize))>,
+	next: usize,
 }
 
-pub struct DepthFirst<'a> {
-	map: &'a mut Map<CellType>
+impl Frame {
+	pub fn init(map: &Map<CellType>, cursor: (usize, usize), direction: Option<Direction>, cost: usize) -> Frame {
+		Frame { cursor, direction, cost, neighbours: map.adjacents(cursor), next: 0 }
+	}
+
+	/// Moves on to the next neighbour that's yet to be tried
+	pub fn next_neighbour(&mut self) -> Option<(Direction, (usize, usize))> {
+		let neighbour = self.neighbours.get(self.next).copied();
+		self.next += 1;
+		neighbour
+	}
+}
+
+/// Pieces together the directions taken to reach the top of the stack, then `last` on top of that
+pub(crate) fn stack_path(stack: &[Frame], last: Option<Direction>) -> Vec<Direction> {
+	stack.iter()
+		.filter_map(|frame| frame.direction)
+		.chain(last)
+		.collect()
+}
+
+/// Implementation of depth-first, where all the travel history are stored in an explicit stack
+pub struct DepthFirst<'a, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	stack: Vec<Frame>,
+	visited: Visited,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
 }
 
-impl<'a> Search for DepthFirst<'a> {
-	fn search(&mut self) -> SearchReport {
-		let path = self.recurse(self.map.initial);
+impl<'a, W> Search for DepthFirst<'a, W> where
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
 
-		return match path {
-			SearchResult::Miss(count) => {
-				SearchReport { search_nodes: count, solution: None }
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
 			}
-			SearchResult::Hit(count, mut path) => {
-				// As it recurses it does not shift elements on so it has to be reversed
-				path.reverse();
-				SearchReport { search_nodes: count, solution: Some(path) }
+			if self.tally.is_finished() {
+				return None;
 			}
-		};
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.visited, limit);
+				continue;
+			}
+
+			let top = match self.stack.last_mut() {
+				Some(top) => top,
+				None => {
+					self.tally.exhausted(&self.visited);
+					continue;
+				}
+			};
+
+			match top.next_neighbour() {
+				Some((dir, pos)) => self.enter(Some(dir), pos),
+				// Every neighbour has been tried, so backtrack
+				None => { self.stack.pop(); }
+			}
+		}
 	}
 }
 
 impl<'a> DepthFirst<'a> {
-	pub fn init(map: &mut Map<CellType>) -> DepthFirst {
-		DepthFirst { map }
+	pub fn init(map: &'a Map<CellType>) -> DepthFirst<'a> {
+		DepthFirst::with_weigher(map, UniformMoveWeight)
 	}
+}
+
+impl<'a, W> DepthFirst<'a, W> where
+	W: MoveWeight {
+	/// Same as [`DepthFirst::init`], but the solution is costed with the given weigher
+	pub fn with_weigher(map: &'a Map<CellType>, weigher: W) -> DepthFirst<'a, W> {
+		DepthFirst {
+			map,
+			stack: vec![],
+			visited: Visited::init(map),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Clears out the previous run and steps onto the initial cell
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.visited.clear();
+		self.stack.clear();
 
-	/// Implementation of depth-first, where all the travel history are stored in stack
-	fn recurse(&mut self, cur: (usize, usize)) -> SearchResult {
-		let mut count = 1;
-		match self.map.read_cell_mut(cur) {
-			CellType::Target => return SearchResult::Hit(count, vec![]),
-			CellType::Wall(ref mut visited) => *visited = true,
+		self.enter(None, self.map.initial);
+	}
+
+	/// Steps onto a cell from the top of the stack, moving `direction`
+	///
+	/// The height of the stack stands in for the size of the frontier
+	fn enter(&mut self, direction: Option<Direction>, cur: (usize, usize)) {
+		let cost = self.stack.last().map_or(0, |x| x.cost) + direction.map_or(0, |x| self.map.move_cost(&self.weigher, x, cur));
+		if !self.tally.within(self.stack.len(), cost) {
+			return;
+		}
+
+		self.tally.search_nodes += 1;
+		self.tally.generate(cur, self.stack.len() + 1);
+
+		match self.map.read_cell(cur) {
+			CellType::Target => {
+				let path = stack_path(&self.stack, direction);
+				self.tally.found(self.map.path_cost(&self.weigher, &path), cur, path, &self.visited);
+			}
+			CellType::Wall(_) => {
+				self.visited.insert(cur);
+			}
 			// If it's the initial cell or a blank cell
-			CellType::Initial(ref mut visited) |
-			CellType::Blank(ref mut visited) => {
+			CellType::Initial(_) |
+			CellType::Blank(_) => {
 				// Mark current cell or skip if it's marked already
-				if *visited { return SearchResult::Miss(1); }
-				*visited = true;
-
-				for (dir, pos) in self.map.adjacents(cur) {
-					let inner = self.recurse(pos);
-
-					match &inner {
-						SearchResult::Hit(inner_count, _) | SearchResult::Miss(inner_count) =>
-							count += *inner_count
-					}
-
-					if let SearchResult::Hit(_, mut path) = inner {
-						// Added to vector in reverse.
-						path.push(dir); // Alternative (shift on) would mean lots of reallocs
-						return SearchResult::Hit(count, path);
-					}
+				if !self.visited.insert(cur) {
+					self.tally.prune(cur);
+					return;
 				}
+				self.tally.expand(cur);
 
-				return SearchResult::Miss(count);
+				self.stack.push(Frame::init(self.map, cur, direction, cost));
 			}
 			#[cfg(feature = "eyecandy")]
 			_ => {}
 		};
-		return SearchResult::Miss(count);
 	}
-}
\ No newline at end of file
+}
@@ -1,25 +1,28 @@
 use crate::search::{Direction, Cursor, MoveWeight};
 use crate::utils::queue::sorted::Weighted;
+use std::marker::PhantomData;
 
 #[derive(Default, Debug)]
 pub struct DijkstraCursor<W> {
 	// Phantom pointer, for compatibility reasons
 	heuristic_weight: usize,
-	path: Vec<Direction>,
+	cost: usize,
+	node: usize,
 	cursor: (usize, usize),
-	weigher: W,
+	direction: Option<Direction>,
+	_weigher: PhantomData<W>,
 }
 
 impl<W> Cursor for DijkstraCursor<W> where
 	W: MoveWeight {
 	type DirectionWeigher = W;
 
-	fn path(&self) -> &Vec<Direction> {
-		&self.path
+	fn node(&self) -> usize {
+		self.node
 	}
 
-	fn path_mut(&mut self) -> &mut Vec<Direction> {
-		&mut self.path
+	fn node_mut(&mut self) -> &mut usize {
+		&mut self.node
 	}
 
 	fn cursor(&self) -> &(usize, usize) {
@@ -30,8 +33,12 @@ impl<W> Cursor for DijkstraCursor<W> where
 		&mut self.cursor
 	}
 
-	fn into_path(self) -> Vec<Direction> {
-		self.path
+	fn cost_mut(&mut self) -> &mut usize {
+		&mut self.cost
+	}
+
+	fn direction_mut(&mut self) -> &mut Option<Direction> {
+		&mut self.direction
 	}
 }
 
@@ -39,9 +46,7 @@ impl<W> Weighted for DijkstraCursor<W> where
 	W: MoveWeight {
 	/// Dijkstra weight = travelled weight
 	fn weigh(&self) -> usize {
-		self.path.iter()
-			.map(|d| self.weigher.weigh(d))
-			.fold(0, |fold, add| fold + add)
+		self.cost
 	}
 
 	fn heuristic_weight(&mut self) -> &mut usize {
@@ -49,6 +54,6 @@ impl<W> Weighted for DijkstraCursor<W> where
 	}
 
 	fn direction(&self) -> Option<Direction> {
-		self.path.last().map(|x| *x)
+		self.direction
 	}
 }
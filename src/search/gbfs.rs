@@ -1,24 +1,27 @@
 use crate::search::{Direction, Cursor, MoveWeight};
 use crate::utils::queue::sorted::Weighted;
+use std::marker::PhantomData;
 
 #[derive(Default, Debug)]
 pub struct GBFCursor<W> {
 	weight: usize,
-	path: Vec<Direction>,
+	cost: usize,
+	node: usize,
 	cursor: (usize, usize),
-	weigher: W,
+	direction: Option<Direction>,
+	_weigher: PhantomData<W>,
 }
 
 impl<W> Cursor for GBFCursor<W> where
 	W: MoveWeight {
 	type DirectionWeigher = W;
 
-	fn path(&self) -> &Vec<Direction> {
-		&self.path
+	fn node(&self) -> usize {
+		self.node
 	}
 
-	fn path_mut(&mut self) -> &mut Vec<Direction> {
-		&mut self.path
+	fn node_mut(&mut self) -> &mut usize {
+		&mut self.node
 	}
 
 	fn cursor(&self) -> &(usize, usize) {
@@ -29,8 +32,12 @@ impl<W> Cursor for GBFCursor<W> where
 		&mut self.cursor
 	}
 
-	fn into_path(self) -> Vec<Direction> {
-		self.path
+	fn cost_mut(&mut self) -> &mut usize {
+		&mut self.cost
+	}
+
+	fn direction_mut(&mut self) -> &mut Option<Direction> {
+		&mut self.direction
 	}
 }
 
@@ -45,6 +52,6 @@ impl<W> Weighted for GBFCursor<W> {
 	}
 
 	fn direction(&self) -> Option<Direction> {
-		self.path.last().map(|x| *x)
+		self.direction
 	}
 }
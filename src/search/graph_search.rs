@@ -4,73 +4,73 @@ use std::marker::PhantomData;
 use crate::utils::queue::sorted::Weighted;
 use crate::utils::heuristics::Heuristic;
 use crate::search::{Cursor, Search};
-use crate::SearchReport;
+use crate::search::arena::Arena;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
+use crate::utils::visited::Visited;
+use crate::report::Tally;
 
 /// Abstract implementation for a graph search strategy
 ///
 /// - Where it can have a custom heuristic function
 /// - Where it can have a custom queueing strategy
-/// 	- By extension requiring a custom cursor
+///   - By extension requiring a custom cursor
 /// - Where it can have a custom duplication checking strategy
-pub struct GraphSearch<'a, H, Q, C> {
-	map: &'a mut Map<CellType>,
+///   - Or a table of the cheapest known cost per cell, see [`GraphSearch::with_cost_table`]
+pub struct GraphSearch<'a, H, Q, C: Cursor> {
+	map: &'a Map<CellType>,
 	heuristic: H,
 	queue: Q,
 	_cursor: PhantomData<C>,
-	filter: fn(&Map<CellType>, &C) -> bool,
+	filter: fn(&Visited, &Arena, &C) -> bool,
+	visited: Visited,
+	arena: Arena,
+	weigher: C::DirectionWeigher,
+	/// Cheapest known cost to each cell, keyed by [`Map::index`]
+	best_costs: Option<Vec<usize>>,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
 }
 
 impl<'a, H, Q, C> Search for GraphSearch<'a, H, Q, C> where
 	H: Heuristic,
 	C: Cursor + Weighted + Default,
 	Q: QueueStrategy<C>, {
-	fn search(&mut self) -> SearchReport {
-		// Kickstart the search
-		{
-			let mut initial = C::default();
-
-			// Give cursor available information
-			*initial.heuristic_weight() = self.heuristic.estimate(self.map.initial);
-			*initial.cursor_mut() = self.map.initial;
-
-			// Lazily add to queue
-			self.queue.queue(initial);
-		}
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
 
-		let mut count = 0;
-		while let Some(cur) = self.queue.dequeue() {
-			count += 1;
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
 
-			// Use the duplication checking strategy to verify the cell's validity
-			let skip = (self.filter)(self.map, &cur);
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
 
-			// Mark current cell
-			match self.map.read_cell_mut(*cur.cursor()) {
-				// If it's a cell that's not a path or target
-				CellType::Initial(ref mut visited) |
-				CellType::Blank(ref mut visited) |
-				CellType::Wall(ref mut visited) => {
-					if skip {
-						continue;
-					}
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
 
-					*visited = true;
-				}
-				CellType::Target => {
-					return SearchReport { search_nodes: count, solution: Some(cur.into_path()) };
-				}
-				#[cfg(feature = "eyecandy")]
-				_ => continue
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.visited, limit);
+				continue;
 			}
 
-			// If all else goes well, expand this cell's children
-			self.expose_next_layer(cur);
+			match self.queue.dequeue() {
+				Some(cur) => self.visit(cur),
+				None => self.tally.exhausted(&self.visited)
+			}
 		}
-
-		return SearchReport {
-			search_nodes: count,
-			solution: None,
-		};
 	}
 }
 
@@ -78,22 +78,136 @@ impl<'a, H, Q, C> GraphSearch<'a, H, Q, C> where
 	H: Heuristic,
 	C: Cursor + Weighted + Default,
 	Q: QueueStrategy<C> {
-	pub fn init(map: &mut Map<CellType>, h: H, q: Q, filter: fn(&Map<CellType>, &C) -> bool) -> GraphSearch<H, Q, C> {
-		GraphSearch { map, heuristic: h, queue: q, _cursor: PhantomData, filter }
+	pub fn init(map: &'a Map<CellType>, h: H, q: Q, filter: fn(&Visited, &Arena, &C) -> bool) -> GraphSearch<'a, H, Q, C> {
+		GraphSearch {
+			map,
+			heuristic: h,
+			queue: q,
+			_cursor: PhantomData,
+			filter,
+			visited: Visited::init(map),
+			arena: Arena::default(),
+			weigher: C::DirectionWeigher::default(),
+			best_costs: None,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Keep the cheapest known cost of reaching each cell in place of the duplication checking strategy
+	///
+	/// Neighbours are only queued if they improve on the known cost, stale queue entries are skipped,
+	/// and cells already expanded are expanded again when a cheaper route to them turns up.
+	/// This keeps A* optimal even when its heuristic is admissible but not consistent.
+	pub fn with_cost_table(mut self) -> Self {
+		self.best_costs = Some(vec![usize::MAX; self.map.rows * self.map.cols]);
+		self
+	}
+
+	/// Weigh the moves with the given weigher, rather than the default one of the cursor's weigher type
+	pub fn with_weigher(mut self, weigher: C::DirectionWeigher) -> Self {
+		self.weigher = weigher;
+		self
 	}
 
-	fn expose_next_layer(&mut self, mut cur: C) {
+	/// Clears out the previous run and queues up the initial cell
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.visited.clear();
+		self.arena.clear();
+		// Leftovers of a previous run would point into the old tree
+		while self.queue.dequeue().is_some() {}
+		if let Some(costs) = &mut self.best_costs {
+			costs.iter_mut().for_each(|x| *x = usize::MAX);
+			costs[self.map.index(self.map.initial)] = 0;
+		}
+
+		let mut initial = C::default();
+
+		// Give cursor available information
+		*initial.heuristic_weight() = self.heuristic.estimate(self.map.initial);
+		*initial.cursor_mut() = self.map.initial;
+		*initial.node_mut() = self.arena.root(self.map.initial);
+
+		// Lazily add to queue
+		self.queue.queue(initial);
+		self.tally.generate(self.map.initial, self.queue.len());
+	}
+
+	/// Deals with a node that's just been taken off the frontier
+	fn visit(&mut self, cur: C) {
+		self.tally.search_nodes += 1;
+
+		// Use the duplication checking strategy to verify the cell's validity
+		// With the cost table, only entries that have since been beaten by a cheaper route are dropped,
+		// so a closed cell is reopened if it's been reached more cheaply
+		let skip = match &self.best_costs {
+			Some(costs) => self.arena.get(cur.node()).cost > costs[self.map.index(*cur.cursor())],
+			None => (self.filter)(&self.visited, &self.arena, &cur),
+		};
+
+		// Mark current cell
+		match self.map.read_cell(*cur.cursor()) {
+			// If it's a cell that's not a path or target
+			CellType::Initial(_) |
+			CellType::Blank(_) |
+			CellType::Wall(_) => {
+				if skip {
+					self.tally.prune(*cur.cursor());
+					return;
+				}
+
+				self.visited.insert(*cur.cursor());
+			}
+			CellType::Target => {
+				let path = self.arena.path(cur.node());
+				self.tally.found(self.map.path_cost(&self.weigher, &path), *cur.cursor(), path, &self.visited);
+				return;
+			}
+			#[cfg(feature = "eyecandy")]
+			_ => return
+		}
+
+		// If all else goes well, expand this cell's children
+		self.expose_next_layer(cur);
+	}
+
+	fn expose_next_layer(&mut self, cur: C) {
 		match self.map.read_cell(*cur.cursor()) {
 			CellType::Initial(_) | CellType::Blank(_) => { // Unvisited blank, add neighbours to queue
+				self.tally.expand(*cur.cursor());
+
 				for (dir, pos) in self.map.adjacents(*cur.cursor()) {
+					let weight = self.map.move_cost(&self.weigher, dir, pos);
+					let parent = self.arena.get(cur.node());
+
+					if !self.tally.within(parent.depth + 1, parent.cost + weight) {
+						continue;
+					}
+
+					if let Some(costs) = &mut self.best_costs {
+						let cost = parent.cost + weight;
+						let best = &mut costs[self.map.index(pos)];
+
+						if cost >= *best {
+							self.tally.prune(pos);
+							continue;
+						}
+						*best = cost;
+					}
+
 					let mut neighbour = C::default();
+					let node = self.arena.branch(cur.node(), dir, pos, weight);
 
 					*neighbour.heuristic_weight() = self.heuristic.estimate(pos);
 					*neighbour.cursor_mut() = pos;
-					neighbour.path_mut().extend_from_slice(&cur.path_mut());
-					neighbour.path_mut().push(dir);
+					*neighbour.node_mut() = node;
+					*neighbour.cost_mut() = self.arena.get(node).cost;
+					*neighbour.direction_mut() = Some(dir);
 
 					self.queue.queue(neighbour);
+					self.tally.generate(pos, self.queue.len());
 				}
 			}
 			// Walls, Paths etc. are just left alone
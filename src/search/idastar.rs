@@ -0,0 +1,220 @@
+use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight};
+use crate::search::dfs::{Frame, stack_path};
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
+use crate::map::{Map, CellType};
+use crate::utils::heuristics::Heuristic;
+use crate::utils::visited::Visited;
+use crate::report::Tally;
+
+/// A* done depth-first, where each iteration is bounded by the cost plus heuristic estimate of the paths it explores,
+/// and the bound is lifted to the cheapest estimate that went over it for the next iteration
+///
+/// Only the current path is kept in memory, so cells are only checked against the path they're on for repetition.
+/// That keeps the memory down to the depth of the search, but a cell can be explored many times along different paths.
+/// The solution is the cheapest as long as the heuristic never overestimates.
+///
+/// On maps with lots of open space the number of paths blows up, see [`IterativeDeepeningAStar::with_cost_table`]
+pub struct IterativeDeepeningAStar<'a, H, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	heuristic: H,
+	stack: Vec<Frame>,
+	/// Cells on the current path
+	on_path: Visited,
+	/// Every cell touched across all the iterations
+	touched: Visited,
+	/// Bound on the estimated cost of the paths in the current iteration
+	threshold: usize,
+	/// Smallest estimate that's gone over the bound, which is the bound for the next iteration
+	next_threshold: usize,
+	/// Cheapest cost each cell has been reached for in the current iteration, keyed by [`Map::index`]
+	best_costs: Option<Vec<usize>>,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
+}
+
+impl<'a, H, W> Search for IterativeDeepeningAStar<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.touched, limit);
+				continue;
+			}
+
+			let top = match self.stack.last_mut() {
+				Some(top) => top,
+				None => {
+					self.deepen();
+					continue;
+				}
+			};
+
+			match top.next_neighbour() {
+				Some((dir, pos)) => self.enter(Some(dir), pos),
+				// Every neighbour has been tried, so backtrack
+				None => {
+					let frame = self.stack.pop().expect("checked to be non-empty");
+					self.on_path.remove(frame.cursor);
+				}
+			}
+		}
+	}
+}
+
+impl<'a, H> IterativeDeepeningAStar<'a, H> where
+	H: Heuristic {
+	pub fn init(map: &'a Map<CellType>, heuristic: H) -> IterativeDeepeningAStar<'a, H> {
+		IterativeDeepeningAStar::with_weigher(map, heuristic, UniformMoveWeight)
+	}
+}
+
+impl<'a, H, W> IterativeDeepeningAStar<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	/// Same as [`IterativeDeepeningAStar::init`], but the moves are costed with the given weigher
+	pub fn with_weigher(map: &'a Map<CellType>, heuristic: H, weigher: W) -> IterativeDeepeningAStar<'a, H, W> {
+		IterativeDeepeningAStar {
+			map,
+			heuristic,
+			stack: vec![],
+			on_path: Visited::init(map),
+			touched: Visited::init(map),
+			threshold: 0,
+			next_threshold: usize::MAX,
+			best_costs: None,
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Remember the cheapest cost of reaching each cell within an iteration,
+	/// so that a cell isn't explored again unless it's reached more cheaply
+	///
+	/// This trades the memory of a cost per cell for not going down the same paths over and over,
+	/// the solution is still the cheapest.
+	pub fn with_cost_table(mut self) -> Self {
+		self.best_costs = Some(vec![usize::MAX; self.map.rows * self.map.cols]);
+		self
+	}
+
+	/// Clears out the previous run and starts the first iteration, bounded by the estimate of the initial cell
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.touched.clear();
+		self.stack.clear();
+		self.threshold = self.heuristic.estimate(self.map.initial);
+
+		self.iterate();
+	}
+
+	/// Starts an iteration over from the initial cell
+	fn iterate(&mut self) {
+		self.on_path.clear();
+		self.next_threshold = usize::MAX;
+		if let Some(costs) = &mut self.best_costs {
+			costs.iter_mut().for_each(|x| *x = usize::MAX);
+		}
+		// Only the nodes of the last iteration are counted as search nodes
+		self.tally.search_nodes = 0;
+
+		self.enter(None, self.map.initial);
+	}
+
+	/// Lifts the bound once an iteration has been exhausted, unless nothing went over it
+	fn deepen(&mut self) {
+		if self.next_threshold == usize::MAX {
+			self.tally.exhausted(&self.touched);
+		} else {
+			self.threshold = self.next_threshold;
+			self.iterate();
+		}
+	}
+
+	/// Steps onto a cell from the top of the stack, moving `direction`
+	///
+	/// The height of the stack stands in for the size of the frontier
+	fn enter(&mut self, direction: Option<Direction>, cur: (usize, usize)) {
+		let cost = self.stack.last().map_or(0, |x| x.cost) + direction.map_or(0, |x| self.map.move_cost(&self.weigher, x, cur));
+		if !self.tally.within(self.stack.len(), cost) {
+			return;
+		}
+
+		self.tally.search_nodes += 1;
+		self.tally.generate(cur, self.stack.len() + 1);
+		self.touched.insert(cur);
+
+		if let CellType::Wall(_) = self.map.read_cell(cur) {
+			return;
+		}
+
+		// Paths estimated to go over the bound are left for a later iteration, including the ones onto a target,
+		// as there could be a cheaper path to a target within the bound
+		let estimate = cost + self.heuristic.estimate(cur);
+		if estimate > self.threshold {
+			self.next_threshold = self.next_threshold.min(estimate);
+			return;
+		}
+
+		match self.map.read_cell(cur) {
+			CellType::Target => {
+				let path = stack_path(&self.stack, direction);
+				self.tally.found(self.map.path_cost(&self.weigher, &path), cur, path, &self.touched);
+			}
+			// If it's the initial cell or a blank cell
+			CellType::Initial(_) |
+			CellType::Blank(_) => {
+				// Going around in circles, or somewhere that's been reached for less already
+				if self.on_path.contains(cur) || !self.improves(cur, cost) {
+					self.tally.prune(cur);
+					return;
+				}
+				self.on_path.insert(cur);
+				self.tally.expand(cur);
+
+				self.stack.push(Frame::init(self.map, cur, direction, cost));
+			}
+			_ => {}
+		};
+	}
+
+	/// Whether reaching the cell for `cost` beats the cost table, noting it down if it does; always true without the table
+	fn improves(&mut self, cur: (usize, usize), cost: usize) -> bool {
+		let index = self.map.index(cur);
+		match &mut self.best_costs {
+			Some(costs) if cost >= costs[index] => false,
+			Some(costs) => {
+				costs[index] = cost;
+				true
+			}
+			None => true
+		}
+	}
+}
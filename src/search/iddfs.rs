@@ -1,116 +1,174 @@
-use crate::search::Search;
+use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight};
+use crate::search::dfs::{Frame, stack_path};
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
 use crate::map::{Map, CellType};
-use crate::SearchReport;
-use crate::search::dfs::SearchResult;
+use crate::utils::visited::Visited;
+use crate::report::Tally;
 
-pub struct IterativeDeepening<'a> {
-	map: &'a mut Map<CellType>,
+/// Depth-first search with a depth limit, which is lifted by one for each iteration that's cut short by it
+pub struct IterativeDeepening<'a, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	stack: Vec<Frame>,
+	/// Depth limit of the current iteration
+	limit: usize,
+	/// Whether the current iteration has left cells unexplored because of the limit
+	more: bool,
+	/// Closed set of the current iteration
+	visited: Visited,
+	/// Every cell touched across all the iterations
+	touched: Visited,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
 }
 
-impl<'a> Search for IterativeDeepening<'a> {
-	fn search(&mut self) -> SearchReport {
-		let mut limit = 0;
-		let mut map = self.map.to_owned();
+impl<'a, W> Search for IterativeDeepening<'a, W> where
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
 		loop {
-			let (result, more) = self.recurse(self.map.initial, limit);
-
-			match result {
-				SearchResult::Hit(count, mut res) => {
-					res.reverse();
-
-					// Clean up the output if need be
-					#[cfg(feature = "eyecandy")]
-						map.iterate(|(x, y), old| {
-						match old {
-							CellType::Initial(old_cv) |
-							CellType::Blank(old_cv) |
-							CellType::Wall(old_cv) => {
-								if !*old_cv {
-									return;
-								}
-							}
-							_ => return
-						}
-
-						match self.map.read_cell_mut((x, y)) {
-							CellType::Initial(ref mut cv) |
-							CellType::Blank(ref mut cv) |
-							CellType::Wall(ref mut cv) => {
-								*cv = true
-							}
-							_ => return
-						}
-					});
-
-					return SearchReport {
-						search_nodes: count,
-						solution: Some(res),
-					};
-				}
-				SearchResult::Miss(count) => {
-					if more {
-						limit += 1;
-
-						map = self.map.to_owned();
-						self.map.clear_visits();
-					} else {
-						return SearchReport {
-							search_nodes: count,
-							solution: None,
-						};
-					}
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.touched.union(&self.visited);
+				self.tally.cut_off(&self.touched, limit);
+				continue;
+			}
+
+			let top = match self.stack.last_mut() {
+				Some(top) => top,
+				None => {
+					self.deepen();
+					continue;
 				}
+			};
+
+			match top.next_neighbour() {
+				Some((dir, pos)) => self.enter(Some(dir), pos),
+				// Every neighbour has been tried, so backtrack
+				None => { self.stack.pop(); }
 			}
 		}
 	}
 }
 
 impl<'a> IterativeDeepening<'a> {
-	pub fn init(map: &mut Map<CellType>) -> IterativeDeepening {
-		IterativeDeepening { map }
+	pub fn init(map: &'a Map<CellType>) -> IterativeDeepening<'a> {
+		IterativeDeepening::with_weigher(map, UniformMoveWeight)
 	}
+}
 
-	/// The "deepening" part of "Iterative Deepening"
-	fn recurse(&mut self, cur: (usize, usize), lim: usize) -> (SearchResult, bool) {
-		let mut count = 1;
-		match self.map.read_cell_mut(cur) {
-			CellType::Target => return (SearchResult::Hit(count, vec![]), false),
-			CellType::Wall(ref mut cv) => {
-				*cv = true;
-			}
-			// If it's the initial cell or a blank cell
-			CellType::Initial(ref mut cv) |
-			CellType::Blank(ref mut cv) => {
-				if lim == 0 {
-					return (SearchResult::Miss(count), !*cv);
-				}
+impl<'a, W> IterativeDeepening<'a, W> where
+	W: MoveWeight {
+	/// Same as [`IterativeDeepening::init`], but the solution is costed with the given weigher
+	pub fn with_weigher(map: &'a Map<CellType>, weigher: W) -> IterativeDeepening<'a, W> {
+		IterativeDeepening {
+			map,
+			stack: vec![],
+			limit: 0,
+			more: false,
+			visited: Visited::init(map),
+			touched: Visited::init(map),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
 
-				if *cv { return (SearchResult::Miss(count), false); }
-				*cv = true; // Cosmetic visited
+	/// Clears out the previous run and starts the first iteration
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.touched.clear();
+		self.stack.clear();
+		self.limit = 0;
 
-				let mut inner_has_more = false;
-				for (dir, pos) in self.map.adjacents(cur) {
-					let (inner, more) = self.recurse(pos, lim - 1);
+		self.iterate();
+	}
 
-					match &inner {
-						SearchResult::Hit(inner_count, _) | SearchResult::Miss(inner_count) =>
-							count += *inner_count
-					}
+	/// Starts an iteration over from the initial cell
+	fn iterate(&mut self) {
+		self.visited.clear();
+		self.more = false;
+		// Only the nodes of the last iteration are counted as search nodes
+		self.tally.search_nodes = 0;
 
-					if let SearchResult::Hit(_, mut path) = inner {
-						// Added to vector in reverse.
-						path.push(dir); // Alternative (shift on) would mean lots of reallocs
-						return (SearchResult::Hit(count, path), more);
-					}
+		self.enter(None, self.map.initial);
+	}
+
+	/// The "deepening" part of "Iterative Deepening", run once an iteration has been exhausted
+	fn deepen(&mut self) {
+		self.touched.union(&self.visited);
+
+		// Going any deeper than the depth limit turns into a cut off
+		if self.more && self.tally.within(self.limit + 1, 0) {
+			self.limit += 1;
+			self.iterate();
+		} else {
+			self.tally.exhausted(&self.touched);
+		}
+	}
+
+	/// Steps onto a cell from the top of the stack, moving `direction`
+	///
+	/// The height of the stack stands in for the size of the frontier
+	fn enter(&mut self, direction: Option<Direction>, cur: (usize, usize)) {
+		let cost = self.stack.last().map_or(0, |x| x.cost) + direction.map_or(0, |x| self.map.move_cost(&self.weigher, x, cur));
+		if !self.tally.within(self.stack.len(), cost) {
+			return;
+		}
+
+		self.tally.search_nodes += 1;
+		self.tally.generate(cur, self.stack.len() + 1);
+
+		match self.map.read_cell(cur) {
+			CellType::Target => {
+				let path = stack_path(&self.stack, direction);
+				self.touched.union(&self.visited);
+				self.tally.found(self.map.path_cost(&self.weigher, &path), cur, path, &self.touched);
+			}
+			CellType::Wall(_) => {
+				self.visited.insert(cur);
+			}
+			// If it's the initial cell or a blank cell
+			CellType::Initial(_) |
+			CellType::Blank(_) => {
+				if self.stack.len() == self.limit {
+					self.more |= !self.visited.contains(cur);
+					return;
+				}
 
-					inner_has_more |= more;
+				if !self.visited.insert(cur) {
+					self.tally.prune(cur);
+					return;
 				}
+				self.tally.expand(cur);
 
-				return (SearchResult::Miss(count), inner_has_more);
+				self.stack.push(Frame::init(self.map, cur, direction, cost));
 			}
 			#[cfg(feature = "eyecandy")]
 			_ => {}
 		};
-		return (SearchResult::Miss(count), false);
 	}
 }
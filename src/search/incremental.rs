@@ -0,0 +1,419 @@
+use crate::search::{Direction, MoveWeight, UniformMoveWeight};
+use crate::map::{Map, CellType};
+use crate::utils::heuristics::Heuristic;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Planners that keep their work between runs, and only go back over what's changed when the map does
+///
+/// Meant for agents that find out about walls as they go: change the cells they've seen, then replan from wherever they are
+pub trait Replan {
+	/// Puts a wall up or takes it down, which has to be a [`CellType::Wall`] or a [`CellType::Blank`]
+	///
+	/// Panics on the targets, which can't be changed
+	fn update_cell(&mut self, coords: (usize, usize), cell: CellType);
+
+	/// Makes moves onto the cell cost `cost` times over, see [`Map::set_terrain`]
+	fn update_terrain(&mut self, coords: (usize, usize), cost: usize);
+
+	/// The cheapest path from `from` to a target on the map as it is now, or [`None`] if there's no getting to one
+	fn replan(&mut self, from: (usize, usize)) -> Option<Vec<Direction>>;
+
+	/// The map as it stands after the updates, with its initial cell on where the last plan started from
+	fn map(&self) -> &Map<CellType>;
+
+	/// Number of cells expanded by the last replan
+	fn expanded(&self) -> usize;
+}
+
+/// Priority of a cell on the frontier, the estimated cost of the path through it and then the cost to it
+type Key = (usize, usize);
+
+/// The bookkeeping that LPA* and D* Lite have in common, which only differ in which way they go over the map
+///
+/// Every cell has its cost `g` as of when it was last expanded, and the cost `rhs` it should have going by its neighbours.
+/// Cells where the two differ are queued up to be expanded, and expanding a cell makes it consistent again and updates the cells that go by it.
+struct Costs<W> {
+	map: Map<CellType>,
+	weigher: W,
+	/// Costs go from the cells to the targets rather than from the initial cell to the cells
+	backwards: bool,
+	/// The cells that cost nothing, the initial cell going forwards or the targets going backwards
+	sources: Vec<(usize, usize)>,
+	g: Vec<usize>,
+	rhs: Vec<usize>,
+	queue: BinaryHeap<Reverse<(Key, (usize, usize))>>,
+	/// Key each cell is on the queue with, keyed by [`Map::index`], entries on the queue that don't match are stale
+	queued: Vec<Option<Key>>,
+	/// How much the keys on the queue have fallen behind since the cell planned from moved, only for D* Lite
+	km: usize,
+	expanded: usize,
+}
+
+impl<W> Costs<W> where
+	W: MoveWeight {
+	fn init(map: Map<CellType>, weigher: W, backwards: bool) -> Costs<W> {
+		let size = map.rows * map.cols;
+
+		Costs {
+			map,
+			weigher,
+			backwards,
+			sources: vec![],
+			g: vec![usize::MAX; size],
+			rhs: vec![usize::MAX; size],
+			queue: BinaryHeap::new(),
+			queued: vec![None; size],
+			km: 0,
+			expanded: 0,
+		}
+	}
+
+	/// Throws the costs away and starts over from the `sources`
+	fn restart<H: Heuristic + ?Sized>(&mut self, sources: Vec<(usize, usize)>, heuristic: &H) {
+		self.g.iter_mut().for_each(|x| *x = usize::MAX);
+		self.rhs.iter_mut().for_each(|x| *x = usize::MAX);
+		self.queued.iter_mut().for_each(|x| *x = None);
+		self.queue.clear();
+		self.km = 0;
+
+		self.sources = sources;
+		for source in self.sources.clone() {
+			self.update(source, heuristic);
+		}
+	}
+
+	fn is_wall(&self, cell: (usize, usize)) -> bool {
+		matches!(self.map.read_cell(cell), CellType::Wall(_))
+	}
+
+	/// The moves the cost of the cell goes by, with the cell at the other end and the weight of the move
+	///
+	/// Going forwards they're the moves onto the cell, and going backwards the moves off it
+	fn upstream(&self, cell: (usize, usize)) -> Vec<(Direction, (usize, usize), usize)> {
+		if self.is_wall(cell) {
+			return vec![];
+		}
+
+		let moves = if self.backwards { self.map.adjacents(cell) } else { self.map.predecessors(cell) };
+		moves.into_iter()
+			.filter(|(_, other)| !self.is_wall(*other))
+			.map(|(dir, other)| {
+				let onto = if self.backwards { other } else { cell };
+				(dir, other, self.map.move_cost(&self.weigher, dir, onto))
+			})
+			.collect()
+	}
+
+	/// The cells whose costs go by this one
+	fn downstream(&self, cell: (usize, usize)) -> Vec<(usize, usize)> {
+		if self.is_wall(cell) {
+			return vec![];
+		}
+
+		let moves = if self.backwards { self.map.predecessors(cell) } else { self.map.adjacents(cell) };
+		moves.into_iter()
+			.map(|(_, other)| other)
+			.filter(|other| !self.is_wall(*other))
+			.collect()
+	}
+
+	fn key<H: Heuristic + ?Sized>(&self, cell: (usize, usize), heuristic: &H) -> Key {
+		let i = self.map.index(cell);
+		let cost = self.g[i].min(self.rhs[i]);
+		(cost.saturating_add(heuristic.estimate(cell)).saturating_add(self.km), cost)
+	}
+
+	/// Works out what the cell should cost going by its neighbours, and queues it up if that's not what it costs
+	fn update<H: Heuristic + ?Sized>(&mut self, cell: (usize, usize), heuristic: &H) {
+		let i = self.map.index(cell);
+
+		self.rhs[i] = if self.is_wall(cell) {
+			usize::MAX
+		} else if self.sources.contains(&cell) {
+			0
+		} else {
+			self.upstream(cell).into_iter()
+				.filter(|(_, other, _)| self.g[self.map.index(*other)] != usize::MAX)
+				.map(|(_, other, weight)| self.g[self.map.index(other)] + weight)
+				.min()
+				.unwrap_or(usize::MAX)
+		};
+
+		self.queued[i] = None;
+		if self.g[i] != self.rhs[i] {
+			let key = self.key(cell, heuristic);
+			self.queued[i] = Some(key);
+			self.queue.push(Reverse((key, cell)));
+		}
+	}
+
+	/// The lowest key on the queue, throwing out the stale entries on top
+	fn top(&mut self) -> Option<(Key, (usize, usize))> {
+		while let Some(Reverse((key, cell))) = self.queue.peek().copied() {
+			if self.queued[self.map.index(cell)] == Some(key) {
+				return Some((key, cell));
+			}
+			self.queue.pop();
+		}
+		None
+	}
+
+	/// Expands cells until the cheapest of the `ends` has its cost settled, and returns it
+	fn settle<H: Heuristic + ?Sized>(&mut self, ends: &[(usize, usize)], heuristic: &H) -> Option<(usize, usize)> {
+		self.expanded = 0;
+
+		loop {
+			let end = *ends.iter().min_by_key(|x| self.key(**x, heuristic))?;
+			let i = self.map.index(end);
+
+			let (key, cell) = match self.top() {
+				Some(top) if top.0 < self.key(end, heuristic) || self.g[i] != self.rhs[i] => top,
+				_ => return Some(end).filter(|_| self.g[i] != usize::MAX)
+			};
+			self.queue.pop();
+			let j = self.map.index(cell);
+			self.queued[j] = None;
+
+			// Queued before the cell planned from moved, so it goes back on with what it's keyed with now
+			let current = self.key(cell, heuristic);
+			if key < current {
+				self.queued[j] = Some(current);
+				self.queue.push(Reverse((current, cell)));
+				continue;
+			}
+
+			self.expanded += 1;
+			if self.g[j] > self.rhs[j] {
+				self.g[j] = self.rhs[j];
+			} else {
+				// Costs more than it used to, so it's worked out again along with everything that went by it
+				self.g[j] = usize::MAX;
+				self.update(cell, heuristic);
+			}
+
+			for other in self.downstream(cell) {
+				self.update(other, heuristic);
+			}
+		}
+	}
+
+	/// Goes down the cheapest moves from `end` to a source, returning the moves made in the order they're made on the map
+	///
+	/// Searched breadth-first over every move that keeps to the cheapest cost, since moves that weigh nothing can go around in circles
+	fn route(&self, end: (usize, usize)) -> Option<Vec<Direction>> {
+		let mut parents: Vec<Option<(Direction, (usize, usize))>> = vec![None; self.map.rows * self.map.cols];
+		let mut queue = VecDeque::from([end]);
+		let mut seen = vec![false; self.map.rows * self.map.cols];
+		seen[self.map.index(end)] = true;
+
+		while let Some(cell) = queue.pop_front() {
+			if self.sources.contains(&cell) {
+				let mut path = vec![];
+				let mut cur = cell;
+				while let Some((dir, prev)) = parents[self.map.index(cur)] {
+					path.push(dir);
+					cur = prev;
+				}
+				// Going backwards, the moves were picked up from the target back to where the agent is
+				if self.backwards {
+					path.reverse();
+				}
+				return Some(path);
+			}
+
+			let moves = self.upstream(cell);
+			let cheapest = moves.iter()
+				.filter(|(_, other, _)| self.g[self.map.index(*other)] != usize::MAX)
+				.map(|(_, other, weight)| self.g[self.map.index(*other)] + weight)
+				.min();
+
+			for (dir, other, weight) in moves {
+				let i = self.map.index(other);
+				if seen[i] || self.g[i] == usize::MAX || Some(self.g[i] + weight) != cheapest {
+					continue;
+				}
+				seen[i] = true;
+				parents[i] = Some((dir, cell));
+				queue.push_back(other);
+			}
+		}
+
+		None
+	}
+
+	/// Changes the map, and updates every cell within two cells of the change,
+	/// which covers every move that lands on it, leaves it or squeezes past it
+	fn edit<H, F>(&mut self, coords: (usize, usize), heuristic: &H, f: F) where
+		H: Heuristic + ?Sized,
+		F: FnOnce(&mut Map<CellType>) {
+		f(&mut self.map);
+
+		let (x, y) = coords;
+		for ny in y.saturating_sub(2)..(y + 3).min(self.map.rows) {
+			for nx in x.saturating_sub(2)..(x + 3).min(self.map.cols) {
+				self.update((nx, ny), heuristic);
+			}
+		}
+	}
+
+	/// Moves the initial cell of the map, leaving the targets be
+	fn move_initial(&mut self, from: (usize, usize)) {
+		if let CellType::Initial(_) = self.map.read_cell(self.map.initial) {
+			*self.map.read_cell_mut(self.map.initial) = CellType::Blank(false);
+		}
+		self.map.initial = from;
+		if let CellType::Blank(_) = self.map.read_cell(from) {
+			*self.map.read_cell_mut(from) = CellType::Initial(false);
+		}
+	}
+
+	/// Checks the change can be made, see [`Replan::update_cell`]
+	fn check_update(&self, coords: (usize, usize), cell: CellType) {
+		assert!(matches!(cell, CellType::Wall(_) | CellType::Blank(_)), "only walls and blank cells can be put down");
+		assert!(!self.map.targets.contains(&coords), "the targets can't be changed");
+	}
+}
+
+/// Lifelong Planning A*, A* that keeps its costs from the initial cell and repairs them as the map changes
+///
+/// Replanning after a change only expands the cells whose costs it changed, which is usually a lot less than planning from scratch.
+/// The plans start from the initial cell, so replanning from another cell throws the costs away, see [`DStarLite`] for an agent on the move.
+/// The plans are the cheapest as long as the heuristic is consistent.
+pub struct LifelongPlanningAStar<H, W = UniformMoveWeight> {
+	costs: Costs<W>,
+	heuristic: H,
+	/// Whether the costs have been worked out from the initial cell
+	started: bool,
+}
+
+impl<H> LifelongPlanningAStar<H> where
+	H: Heuristic {
+	pub fn init(map: Map<CellType>, heuristic: H) -> LifelongPlanningAStar<H> {
+		LifelongPlanningAStar::with_weigher(map, heuristic, UniformMoveWeight)
+	}
+}
+
+impl<H, W> LifelongPlanningAStar<H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	/// Same as [`LifelongPlanningAStar::init`], but the moves are costed with the given weigher
+	pub fn with_weigher(map: Map<CellType>, heuristic: H, weigher: W) -> LifelongPlanningAStar<H, W> {
+		LifelongPlanningAStar {
+			costs: Costs::init(map, weigher, false),
+			heuristic,
+			started: false,
+		}
+	}
+}
+
+impl<H, W> Replan for LifelongPlanningAStar<H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	fn update_cell(&mut self, coords: (usize, usize), cell: CellType) {
+		self.costs.check_update(coords, cell);
+		self.costs.edit(coords, &self.heuristic, |map| *map.read_cell_mut(coords) = cell);
+	}
+
+	fn update_terrain(&mut self, coords: (usize, usize), cost: usize) {
+		self.costs.edit(coords, &self.heuristic, |map| map.set_terrain(coords, cost));
+	}
+
+	fn replan(&mut self, from: (usize, usize)) -> Option<Vec<Direction>> {
+		if !self.started || from != self.costs.map.initial {
+			self.costs.move_initial(from);
+			self.costs.restart(vec![from], &self.heuristic);
+			self.started = true;
+		}
+
+		let targets = self.costs.map.targets.clone();
+		let end = self.costs.settle(&targets, &self.heuristic)?;
+		self.costs.route(end)
+	}
+
+	fn map(&self) -> &Map<CellType> {
+		&self.costs.map
+	}
+
+	fn expanded(&self) -> usize {
+		self.costs.expanded
+	}
+}
+
+/// D* Lite, LPA* run backwards from the targets, so the costs still hold as the agent moves towards them
+///
+/// The heuristic estimates the cost of getting from the agent to a cell, and is aimed anew out of the map every time the agent moves,
+/// as in `DStarLite::init(map, ManhattanHeuristic::from_initial)`. The keys on the queue are kept from before the move,
+/// and only brought up to date as they come off the queue. The plans are the cheapest as long as the heuristic is consistent.
+pub struct DStarLite<F, H, W = UniformMoveWeight> {
+	costs: Costs<W>,
+	/// Aims the heuristic at the initial cell of the map
+	aim: F,
+	heuristic: H,
+	/// Whether the costs have been worked out from the targets
+	started: bool,
+}
+
+impl<F, H> DStarLite<F, H> where
+	F: Fn(&Map<CellType>) -> H,
+	H: Heuristic {
+	pub fn init(map: Map<CellType>, aim: F) -> DStarLite<F, H> {
+		DStarLite::with_weigher(map, aim, UniformMoveWeight)
+	}
+}
+
+impl<F, H, W> DStarLite<F, H, W> where
+	F: Fn(&Map<CellType>) -> H,
+	H: Heuristic,
+	W: MoveWeight {
+	/// Same as [`DStarLite::init`], but the moves are costed with the given weigher
+	pub fn with_weigher(map: Map<CellType>, aim: F, weigher: W) -> DStarLite<F, H, W> {
+		let heuristic = aim(&map);
+
+		DStarLite {
+			costs: Costs::init(map, weigher, true),
+			aim,
+			heuristic,
+			started: false,
+		}
+	}
+}
+
+impl<F, H, W> Replan for DStarLite<F, H, W> where
+	F: Fn(&Map<CellType>) -> H,
+	H: Heuristic,
+	W: MoveWeight {
+	fn update_cell(&mut self, coords: (usize, usize), cell: CellType) {
+		self.costs.check_update(coords, cell);
+		self.costs.edit(coords, &self.heuristic, |map| *map.read_cell_mut(coords) = cell);
+	}
+
+	fn update_terrain(&mut self, coords: (usize, usize), cost: usize) {
+		self.costs.edit(coords, &self.heuristic, |map| map.set_terrain(coords, cost));
+	}
+
+	fn replan(&mut self, from: (usize, usize)) -> Option<Vec<Direction>> {
+		if from != self.costs.map.initial {
+			// The keys on the queue are off by no more than the estimate between where the agent was and where it is
+			self.costs.km += self.heuristic.estimate(from);
+			self.costs.move_initial(from);
+			self.heuristic = (self.aim)(&self.costs.map);
+		}
+		if !self.started {
+			let targets = self.costs.map.targets.clone();
+			self.costs.restart(targets, &self.heuristic);
+			self.started = true;
+		}
+
+		let end = self.costs.settle(&[from], &self.heuristic)?;
+		self.costs.route(end)
+	}
+
+	fn map(&self) -> &Map<CellType> {
+		&self.costs.map
+	}
+
+	fn expanded(&self) -> usize {
+		self.costs.expanded
+	}
+}
@@ -0,0 +1,280 @@
+use crate::search::{Search, Cursor, Direction, MoveWeight, UniformMoveWeight};
+use crate::search::arena::Arena;
+use crate::search::astar::AStarCursor;
+use crate::search::steps::SearchEvent;
+use crate::search::limits::SearchLimits;
+use crate::map::{Map, CellType};
+use crate::map::movement::{Movement, Corners};
+use crate::utils::heuristics::Heuristic;
+use crate::utils::queue::QueueStrategy;
+use crate::utils::queue::sorted::{SortedQueue, Weighted};
+use crate::utils::visited::Visited;
+use crate::report::Tally;
+
+/// Jump Point Search, A* that skips over the stretches of open space between the cells where a path might have to turn
+///
+/// Four-way, paths go sideways first and then up or down.
+/// Going sideways, every cell is also scanned up and down; going up or down, a turn is only considered
+/// where a wall beside the previous cell kept the path from turning earlier.
+///
+/// Eight-way, paths go diagonally first and then straight, with every cell on a diagonal scanned along both of its axes.
+/// Going straight, it stops wherever a wall beside the previous cell ends, as the path could have to turn around it.
+/// This is the variant for [`Corners::Avoid`], the other corner rules aren't supported.
+///
+/// Only the jump points make it onto the queue, and the moves in between are filled back in when the path is pieced together.
+/// The moves have to be weighed the same whichever way they go, so [`UniformMoveWeight`] four-way
+/// or [`EuclideanMoveWeight`](crate::search::EuclideanMoveWeight) eight-way, and every cell has to be plain ground.
+pub struct JumpPoint<'a, H, W = UniformMoveWeight> {
+	map: &'a Map<CellType>,
+	heuristic: H,
+	queue: SortedQueue<AStarCursor<W>>,
+	arena: Arena,
+	/// Cheapest known cost to each cell, keyed by [`Map::index`]
+	best_costs: Vec<usize>,
+	visited: Visited,
+	weigher: W,
+	tally: Tally,
+	record: bool,
+	limits: SearchLimits,
+}
+
+impl<'a, H, W> Search for JumpPoint<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	fn record_expansions(&mut self, record: bool) {
+		self.record = record;
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		self.limits = limits;
+	}
+
+	fn reset(&mut self) {
+		self.tally = Tally::idle();
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		if self.tally.is_idle() {
+			self.kickstart();
+		}
+
+		loop {
+			if let Some(event) = self.tally.next_event() {
+				return Some(event);
+			}
+			if self.tally.is_finished() {
+				return None;
+			}
+			if let Some(limit) = self.tally.overrun() {
+				self.tally.cut_off(&self.visited, limit);
+				continue;
+			}
+
+			match self.queue.dequeue() {
+				Some(cur) => self.visit(cur),
+				None => self.tally.exhausted(&self.visited)
+			}
+		}
+	}
+}
+
+impl<'a, H> JumpPoint<'a, H> where
+	H: Heuristic {
+	pub fn init(map: &'a Map<CellType>, heuristic: H) -> JumpPoint<'a, H> {
+		JumpPoint::with_weigher(map, heuristic, UniformMoveWeight)
+	}
+}
+
+impl<'a, H, W> JumpPoint<'a, H, W> where
+	H: Heuristic,
+	W: MoveWeight {
+	/// Same as [`JumpPoint::init`], but the moves are costed with the given weigher
+	///
+	/// Panics if the map isn't one that's [`supports`]ed
+	pub fn with_weigher(map: &'a Map<CellType>, heuristic: H, weigher: W) -> JumpPoint<'a, H, W> {
+		assert!(supports(map), "no jump point search for {:?} or over terrain", map.movement);
+
+		JumpPoint {
+			map,
+			heuristic,
+			queue: SortedQueue::init(),
+			arena: Arena::default(),
+			best_costs: vec![usize::MAX; map.rows * map.cols],
+			visited: Visited::init(map),
+			weigher,
+			tally: Tally::idle(),
+			record: false,
+			limits: SearchLimits::default(),
+		}
+	}
+
+	/// Clears out the previous run and queues up the initial cell
+	fn kickstart(&mut self) {
+		self.tally = Tally::start(self.record, self.limits.clone());
+		self.visited.clear();
+		self.arena.clear();
+		self.queue = SortedQueue::init();
+		self.best_costs.iter_mut().for_each(|x| *x = usize::MAX);
+
+		let node = self.arena.root(self.map.initial);
+		self.push(node);
+	}
+
+	/// Puts a node on the queue as the cheapest way of reaching its cell
+	fn push(&mut self, node: usize) {
+		let cell = self.arena.get(node);
+		let index = self.map.index(cell.cursor);
+		self.best_costs[index] = cell.cost;
+
+		let mut cursor = AStarCursor::default();
+		*cursor.heuristic_weight() = self.heuristic.estimate(cell.cursor);
+		*cursor.cursor_mut() = cell.cursor;
+		*cursor.node_mut() = node;
+		*cursor.cost_mut() = cell.cost;
+		*cursor.direction_mut() = cell.direction;
+
+		self.queue.queue(cursor);
+		self.tally.generate(cell.cursor, self.queue.len());
+	}
+
+	/// Deals with a jump point that's just been taken off the queue
+	fn visit(&mut self, cur: AStarCursor<W>) {
+		self.tally.search_nodes += 1;
+
+		let pos = *cur.cursor();
+		let node = *self.arena.get(cur.node());
+
+		// Stale entry, the cell has since been reached more cheaply
+		if node.cost > self.best_costs[self.map.index(pos)] {
+			self.tally.prune(pos);
+			return;
+		}
+		self.visited.insert(pos);
+
+		if let CellType::Target = self.map.read_cell(pos) {
+			let path = self.arena.path(cur.node());
+			self.tally.found(self.map.path_cost(&self.weigher, &path), pos, path, &self.visited);
+			return;
+		}
+		self.tally.expand(pos);
+
+		for dir in self.directions(pos, node.direction) {
+			let (next, moves) = match self.jump(pos, dir) {
+				Some(x) => x,
+				None => continue
+			};
+
+			let cost = node.cost + moves * self.weigher.weigh(&dir);
+			if !self.tally.within(node.depth + moves, cost) {
+				continue;
+			}
+			if cost >= self.best_costs[self.map.index(next)] {
+				self.tally.prune(next);
+				continue;
+			}
+
+			let child = self.arena.leap(cur.node(), dir, next, moves, cost - node.cost);
+			self.push(child);
+		}
+	}
+
+	/// The directions worth jumping in from a jump point, depending on the direction it was jumped to in
+	fn directions(&self, pos: (usize, usize), from: Option<Direction>) -> Vec<Direction> {
+		let dir = match from {
+			Some(dir) => dir,
+			None => return self.map.movement.directions().to_vec()
+		};
+		let (dx, dy) = dir.delta();
+
+		match self.map.movement {
+			// Going sideways, it can carry on or turn either way
+			Movement::FourWay if dy == 0 => vec![dir, Direction::Up, Direction::Down],
+			// Going up or down, it only turns where the turn was blocked a step earlier
+			Movement::FourWay => {
+				let behind = self.map.adjacent(pos, dir.opposite()).expect("jumped here from behind");
+				let mut dirs = vec![dir];
+				for side in [Direction::Left, Direction::Right] {
+					if self.open(self.map.adjacent(pos, side)) && !self.open(self.map.adjacent(behind, side)) {
+						dirs.push(side);
+					}
+				}
+				dirs
+			}
+			// Diagonally, it can carry on or go straight along either axis
+			_ if dir.is_diagonal() => vec![dir, towards(dx, 0), towards(0, dy)],
+			// Straight, it stopped here because of a wall behind, so it could be turning any way but back
+			_ if dy == 0 => vec![dir, Direction::Up, Direction::Down, towards(dx, -1), towards(dx, 1)],
+			_ => vec![dir, Direction::Left, Direction::Right, towards(-1, dy), towards(1, dy)],
+		}
+	}
+
+	/// Steps along `dir` from `from` until a jump point, returning it with the number of moves made,
+	/// or [`None`] if it runs into a wall first
+	fn jump(&self, from: (usize, usize), dir: Direction) -> Option<((usize, usize), usize)> {
+		let mut cur = from;
+		let mut moves = 0;
+
+		loop {
+			if !self.map.clears_corners(cur, dir) {
+				return None;
+			}
+			let next = self.map.adjacent(cur, dir).filter(|x| self.open(Some(*x)))?;
+			moves += 1;
+
+			if let CellType::Target = self.map.read_cell(next) {
+				return Some((next, moves));
+			}
+			if self.is_jump_point(cur, next, dir) {
+				return Some((next, moves));
+			}
+			cur = next;
+		}
+	}
+
+	/// Whether a jump along `dir` has to stop on `next`, having come from `cur`
+	fn is_jump_point(&self, cur: (usize, usize), next: (usize, usize), dir: Direction) -> bool {
+		let (dx, dy) = dir.delta();
+		// Anywhere it's worth going along either axis from
+		let scan = |dirs: &[Direction]| dirs.iter().any(|x| self.jump(next, *x).is_some());
+		// A wall beside the last cell that's gone from beside this one
+		let forced = |sides: [Direction; 2]| sides.iter().any(|side| {
+			self.open(self.map.adjacent(next, *side)) && !self.open(self.map.adjacent(cur, *side))
+		});
+
+		match self.map.movement {
+			Movement::FourWay if dy == 0 => scan(&[Direction::Up, Direction::Down]),
+			Movement::FourWay => forced([Direction::Left, Direction::Right]),
+			_ if dir.is_diagonal() => scan(&[towards(dx, 0), towards(0, dy)]),
+			_ if dy == 0 => forced([Direction::Up, Direction::Down]),
+			_ => forced([Direction::Left, Direction::Right]),
+		}
+	}
+
+	/// Whether the cell exists and isn't a wall
+	fn open(&self, cell: Option<(usize, usize)>) -> bool {
+		match cell {
+			Some(cell) => !matches!(self.map.read_cell(cell), CellType::Wall(_)),
+			None => false
+		}
+	}
+}
+
+/// Whether there's a way of jumping for the movement of the map, which can't have any terrain
+pub fn supports(map: &Map<CellType>) -> bool {
+	matches!(map.movement, Movement::FourWay | Movement::EightWay(Corners::Avoid)) && !map.has_terrain()
+}
+
+/// The straight or diagonal direction that goes along the signs of `dx` and `dy`
+fn towards(dx: isize, dy: isize) -> Direction {
+	match (dx.signum(), dy.signum()) {
+		(0, -1) => Direction::Up,
+		(-1, 0) => Direction::Left,
+		(0, 1) => Direction::Down,
+		(1, 0) => Direction::Right,
+		(-1, -1) => Direction::UpLeft,
+		(-1, 1) => Direction::DownLeft,
+		(1, 1) => Direction::DownRight,
+		(1, -1) => Direction::UpRight,
+		_ => unreachable!("there's no direction that stays put")
+	}
+}
@@ -0,0 +1,71 @@
+use std::fmt::{self, Display};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Bounds on how much work a search is allowed to do, everything is unbounded by default
+///
+/// A search that runs into any of these ends with a report that's cut off,
+/// which means there might still be a solution beyond the limits
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+	/// Most nodes that can be expanded
+	pub max_nodes: Option<usize>,
+	/// Most moves a path can be made of, longer paths are not explored
+	pub max_depth: Option<usize>,
+	/// Most a path can cost under the search's [`MoveWeight`](crate::search::MoveWeight), costlier paths are not explored
+	pub max_cost: Option<usize>,
+	/// Point in time after which the search gives up
+	pub deadline: Option<Instant>,
+	/// Flag that can be raised from elsewhere, such as another thread, to stop the search
+	pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+	/// Checks the budgets that are used up as the search goes, returning the first one that's run out
+	pub fn overrun(&self, expanded: usize) -> Option<Limit> {
+		if self.max_nodes.is_some_and(|x| expanded >= x) {
+			return Some(Limit::Nodes);
+		}
+		if self.cancel.as_ref().is_some_and(|x| x.load(Ordering::Relaxed)) {
+			return Some(Limit::Cancelled);
+		}
+		if self.deadline.is_some_and(|x| Instant::now() >= x) {
+			return Some(Limit::Deadline);
+		}
+		return None;
+	}
+
+	/// Checks whether a path of `depth` moves costing `cost` is still within bounds, returning the bound it breaks
+	pub fn exceeded(&self, depth: usize, cost: usize) -> Option<Limit> {
+		if self.max_depth.is_some_and(|x| depth > x) {
+			return Some(Limit::Depth);
+		}
+		if self.max_cost.is_some_and(|x| cost > x) {
+			return Some(Limit::Cost);
+		}
+		return None;
+	}
+}
+
+/// The limit that cut a search off, see [`SearchLimits`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Limit {
+	Nodes,
+	Depth,
+	Cost,
+	Deadline,
+	Cancelled,
+}
+
+impl Display for Limit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Limit::Nodes => write!(f, "node limit reached"),
+			Limit::Depth => write!(f, "depth limit reached"),
+			Limit::Cost => write!(f, "cost limit reached"),
+			Limit::Deadline => write!(f, "deadline passed"),
+			Limit::Cancelled => write!(f, "cancelled"),
+		}
+	}
+}
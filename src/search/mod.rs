@@ -4,28 +4,132 @@ pub mod gbfs;
 pub mod astar;
 pub mod dijkstra;
 pub mod iddfs;
+pub mod idastar;
+pub mod bidirectional;
+pub mod jps;
 pub mod wastar;
+pub mod arastar;
+pub mod incremental;
 pub mod graph_search;
+pub mod arena;
+pub mod steps;
+pub mod limits;
 
 #[cfg(test)]
+mod tests;
+#[cfg(all(test, feature = "nightly"))]
 mod bench;
 
 use std::fmt::{Display, self};
+use std::error::Error;
+use std::str::FromStr;
 use std::slice::Iter;
+use crate::search::steps::{SearchEvent, Steps};
+use crate::search::limits::SearchLimits;
 use crate::SearchReport;
 
 /// Interface to define the basic functionality of a search algorithm
+///
+/// Searches run one [`SearchEvent`] at a time through [`Search::step`],
+/// so that they can be paused, capped or interleaved with other work
 pub trait Search {
-	fn search(&mut self) -> SearchReport;
+	/// Runs the search from the start until it finishes
+	fn search(&mut self) -> SearchReport {
+		self.reset();
+		loop {
+			match self.step() {
+				Some(SearchEvent::Found(report)) |
+				Some(SearchEvent::Exhausted(report)) |
+				Some(SearchEvent::CutOff(report)) => return report,
+				Some(_) => continue,
+				None => unreachable!("searches always finish with a report")
+			}
+		}
+	}
+
+	/// Toggles keeping the ordered list of expanded cells in the [`SearchReport`], off by default
+	fn record_expansions(&mut self, record: bool);
+
+	/// Bounds the work done by the search from the next time it starts, unbounded by default
+	fn set_limits(&mut self, limits: SearchLimits);
+
+	/// Throws away the progress of the search, so that the next step starts it over
+	fn reset(&mut self);
+
+	/// Advances the search up to its next event, starting the search if it hasn't been yet
+	///
+	/// Returns [`None`] once the search has finished and its report has been handed out
+	fn step(&mut self) -> Option<SearchEvent>;
+
+	/// Advances the search by at most `expansions` expanded nodes,
+	/// returning the report if the search finished along the way
+	fn advance(&mut self, expansions: usize) -> Option<SearchReport> {
+		let mut remaining = expansions;
+		while remaining > 0 {
+			match self.step()? {
+				SearchEvent::Found(report) |
+				SearchEvent::Exhausted(report) |
+				SearchEvent::CutOff(report) => return Some(report),
+				SearchEvent::Expanded(_) => remaining -= 1,
+				_ => {}
+			}
+		}
+		None
+	}
+
+	/// Iterates the events of the search, picking up from wherever the search is at
+	fn steps(&mut self) -> Steps<'_, Self> where
+		Self: Sized {
+		Steps { search: self }
+	}
+}
+
+/// Lets boxed searches, such as the ones out of [`Method::build`](crate::runner::Method::build), be stepped through too
+impl<S> Search for Box<S> where
+	S: Search + ?Sized {
+	fn search(&mut self) -> SearchReport {
+		(**self).search()
+	}
+
+	fn record_expansions(&mut self, record: bool) {
+		(**self).record_expansions(record)
+	}
+
+	fn set_limits(&mut self, limits: SearchLimits) {
+		(**self).set_limits(limits)
+	}
+
+	fn reset(&mut self) {
+		(**self).reset()
+	}
+
+	fn step(&mut self) -> Option<SearchEvent> {
+		(**self).step()
+	}
 }
 
 /// List of possible directions to take for the intelligent agent
+///
+/// Which of these are allowed is up to the [`Movement`](crate::map::movement::Movement) of the map
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Direction {
 	Up = 0,
 	Left = 1,
 	Down = 2,
 	Right = 3,
+	UpLeft = 4,
+	DownLeft = 5,
+	DownRight = 6,
+	UpRight = 7,
+	/// Knight moves, named after the two cells along and the one across
+	UpUpLeft = 8,
+	LeftLeftUp = 9,
+	LeftLeftDown = 10,
+	DownDownLeft = 11,
+	DownDownRight = 12,
+	RightRightDown = 13,
+	RightRightUp = 14,
+	UpUpRight = 15,
 }
 
 /// Convert the enum into human friendly text representation
@@ -36,6 +140,18 @@ impl Display for Direction {
 			Direction::Left => write!(f, "Left"),
 			Direction::Down => write!(f, "Down"),
 			Direction::Right => write!(f, "Right"),
+			Direction::UpLeft => write!(f, "Up-Left"),
+			Direction::DownLeft => write!(f, "Down-Left"),
+			Direction::DownRight => write!(f, "Down-Right"),
+			Direction::UpRight => write!(f, "Up-Right"),
+			Direction::UpUpLeft => write!(f, "Up-Up-Left"),
+			Direction::LeftLeftUp => write!(f, "Left-Left-Up"),
+			Direction::LeftLeftDown => write!(f, "Left-Left-Down"),
+			Direction::DownDownLeft => write!(f, "Down-Down-Left"),
+			Direction::DownDownRight => write!(f, "Down-Down-Right"),
+			Direction::RightRightDown => write!(f, "Right-Right-Down"),
+			Direction::RightRightUp => write!(f, "Right-Right-Up"),
+			Direction::UpUpRight => write!(f, "Up-Up-Right"),
 		}
 	}
 }
@@ -46,11 +162,68 @@ impl Direction {
 	pub fn iter() -> Iter<'static, Direction> {
 		[Direction::Up, Direction::Left, Direction::Down, Direction::Right].iter()
 	}
+
+	/// The direction that undoes this one
+	pub fn opposite(&self) -> Direction {
+		match self {
+			Direction::Up => Direction::Down,
+			Direction::Left => Direction::Right,
+			Direction::Down => Direction::Up,
+			Direction::Right => Direction::Left,
+			Direction::UpLeft => Direction::DownRight,
+			Direction::DownLeft => Direction::UpRight,
+			Direction::DownRight => Direction::UpLeft,
+			Direction::UpRight => Direction::DownLeft,
+			Direction::UpUpLeft => Direction::DownDownRight,
+			Direction::LeftLeftUp => Direction::RightRightDown,
+			Direction::LeftLeftDown => Direction::RightRightUp,
+			Direction::DownDownLeft => Direction::UpUpRight,
+			Direction::DownDownRight => Direction::UpUpLeft,
+			Direction::RightRightDown => Direction::LeftLeftUp,
+			Direction::RightRightUp => Direction::LeftLeftDown,
+			Direction::UpUpRight => Direction::DownDownLeft,
+		}
+	}
+
+	/// How far the move goes along the columns and the rows, on a square grid
+	pub fn delta(&self) -> (isize, isize) {
+		match self {
+			Direction::Up => (0, -1),
+			Direction::Left => (-1, 0),
+			Direction::Down => (0, 1),
+			Direction::Right => (1, 0),
+			Direction::UpLeft => (-1, -1),
+			Direction::DownLeft => (-1, 1),
+			Direction::DownRight => (1, 1),
+			Direction::UpRight => (1, -1),
+			Direction::UpUpLeft => (-1, -2),
+			Direction::LeftLeftUp => (-2, -1),
+			Direction::LeftLeftDown => (-2, 1),
+			Direction::DownDownLeft => (-1, 2),
+			Direction::DownDownRight => (1, 2),
+			Direction::RightRightDown => (2, 1),
+			Direction::RightRightUp => (2, -1),
+			Direction::UpUpRight => (1, -2),
+		}
+	}
+
+	/// Whether the move goes along both the columns and the rows at once
+	pub fn is_diagonal(&self) -> bool {
+		let (dx, dy) = self.delta();
+		dx != 0 && dy != 0
+	}
 }
 
 /// Experiment: Vary the weights of travelling in different directions to see
 pub trait MoveWeight: Default {
 	fn weigh(&self, d: &Direction) -> usize;
+
+	/// Total weight of travelling along the path
+	fn path_cost(&self, path: &[Direction]) -> usize {
+		path.iter()
+			.map(|d| self.weigh(d))
+			.sum()
+	}
 }
 
 /// Simple uniform-weight direction weigher
@@ -64,34 +237,133 @@ impl MoveWeight for UniformMoveWeight {
 }
 
 /// Weight varies when moving in different directions, as suggested for research initiative
-#[derive(Default)]
-pub struct CustomMoveWeight;
+///
+/// The weights of the four straight moves are data, so they can be set at runtime, see [`FromStr`] for the format.
+/// Moves that go along both axes weigh as much as their steps along each axis put together
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CustomMoveWeight {
+	pub up: usize,
+	pub left: usize,
+	pub down: usize,
+	pub right: usize,
+}
 
-impl CustomMoveWeight {
-	pub fn weigh(d: &Direction) -> usize {
-		match *d {
-			Direction::Up => 4,
-			Direction::Down => 1,
-			Direction::Left => 3,
-			Direction::Right => 2
+/// The weights suggested in the assignment
+impl Default for CustomMoveWeight {
+	fn default() -> Self {
+		CustomMoveWeight {
+			up: 4,
+			left: 3,
+			down: 1,
+			right: 2,
 		}
 	}
 }
 
+/// Diagonal and knight moves weigh as much as their steps along each axis put together
+///
+/// Moves are weighed by the direction they're named for, without the row they're made from,
+/// so the hex moves up and down are charged for going left or right as well, even from the rows where they stay in the same column
 impl MoveWeight for CustomMoveWeight {
 	fn weigh(&self, d: &Direction) -> usize {
-		CustomMoveWeight::weigh(d)
+		match *d {
+			Direction::Up => self.up,
+			Direction::Left => self.left,
+			Direction::Down => self.down,
+			Direction::Right => self.right,
+			_ => {
+				let (dx, dy) = d.delta();
+				let x = if dx < 0 { self.left } else { self.right };
+				let y = if dy < 0 { self.up } else { self.down };
+				x * dx.unsigned_abs() + y * dy.unsigned_abs()
+			}
+		}
+	}
+}
+
+/// Same format as it's read in, `up=4,left=3,down=1,right=2`
+impl Display for CustomMoveWeight {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "up={},left={},down={},right={}", self.up, self.left, self.down, self.right)
+	}
+}
+
+/// Error for when the move weights can't be made sense of
+#[derive(Debug)]
+pub struct InvalidMoveWeights(pub String);
+
+impl Display for InvalidMoveWeights {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "invalid move weights: {}", self.0)
+	}
+}
+
+impl Error for InvalidMoveWeights {}
+
+/// Reads comma separated `direction=weight` pairs, such as `up=4,left=3,down=1,right=2`
+///
+/// Directions left out keep the weight they have by default
+impl FromStr for CustomMoveWeight {
+	type Err = InvalidMoveWeights;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut weights = CustomMoveWeight::default();
+
+		for pair in s.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+			let (direction, weight) = pair.split_once('=')
+				.ok_or_else(|| InvalidMoveWeights(format!("expected direction=weight, got {}", pair)))?;
+			let weight = weight.trim().parse::<usize>()
+				.map_err(|_| InvalidMoveWeights(format!("{} is not an unsigned number", weight.trim())))?;
+
+			match direction.trim().to_ascii_lowercase().as_ref() {
+				"up" => weights.up = weight,
+				"left" => weights.left = weight,
+				"down" => weights.down = weight,
+				"right" => weights.right = weight,
+				x => return Err(InvalidMoveWeights(format!("unknown direction {}", x)))
+			}
+		}
+
+		Ok(weights)
+	}
+}
+
+/// Each move weighs as much as the straight line it covers, scaled up by [`EuclideanMoveWeight::STRAIGHT`] to stay in whole numbers
+///
+/// Diagonal moves come out at 141 and knight moves at 224, which is as close to √2 and √5 times the straight moves as the scale allows
+#[derive(Default)]
+pub struct EuclideanMoveWeight;
+
+impl EuclideanMoveWeight {
+	pub const STRAIGHT: usize = 100;
+	pub const DIAGONAL: usize = 141;
+	pub const KNIGHT: usize = 224;
+}
+
+impl MoveWeight for EuclideanMoveWeight {
+	fn weigh(&self, d: &Direction) -> usize {
+		match d.delta() {
+			(0, _) | (_, 0) => EuclideanMoveWeight::STRAIGHT,
+			(dx, dy) if dx.abs() == dy.abs() => EuclideanMoveWeight::DIAGONAL,
+			_ => EuclideanMoveWeight::KNIGHT
+		}
 	}
 }
 
 /// Define the basic functionality that an abstract Cursor object should have,
 /// for interoperabiltiy of different searches of the common implementation of graph-based search
+///
+/// A cursor is a handle to a node in the search's [`Arena`](arena::Arena),
+/// carrying just enough of the node for the queue to sort it
 pub trait Cursor {
 	type DirectionWeigher: MoveWeight;
 
-	fn path(&self) -> &Vec<Direction>;
-	fn path_mut(&mut self) -> &mut Vec<Direction>;
+	fn node(&self) -> usize;
+	fn node_mut(&mut self) -> &mut usize;
 	fn cursor(&self) -> &(usize, usize);
 	fn cursor_mut(&mut self) -> &mut (usize, usize);
-	fn into_path(self) -> Vec<Direction>;
+	/// The travelled weight, cached from the arena so it's not recomputed on every comparison
+	fn cost_mut(&mut self) -> &mut usize;
+	/// The direction taken to reach this cursor's cell
+	fn direction_mut(&mut self) -> &mut Option<Direction>;
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+use crate::search::Search;
+use crate::SearchReport;
+
+/// Things that happen over the course of a search, as handed out by [`Search::step`]
+#[derive(Clone, Debug)]
+pub enum SearchEvent {
+	/// A node on the cell has been put onto the frontier
+	Generated((usize, usize)),
+	/// A node on the cell has had its neighbours explored
+	Expanded((usize, usize)),
+	/// A node on the cell has been thrown away for being a repeated state
+	Pruned((usize, usize)),
+	/// An anytime search found a solution, or a tighter bound on it, and goes on to improve on it
+	Improved(SearchReport),
+	/// The search reached a target, this is always the last event
+	Found(SearchReport),
+	/// The search ran out of nodes to explore, this is always the last event
+	Exhausted(SearchReport),
+	/// The search ran into one of its [`SearchLimits`](crate::search::limits::SearchLimits), this is always the last event
+	CutOff(SearchReport),
+}
+
+/// Iterator over the [`SearchEvent`]s of a search, see [`Search::steps`]
+///
+/// Dropping the iterator halfway leaves the search where it was, so it can be picked up again later
+pub struct Steps<'s, S: ?Sized> {
+	pub(crate) search: &'s mut S,
+}
+
+impl<'s, S> Iterator for Steps<'s, S> where
+	S: Search + ?Sized {
+	type Item = SearchEvent;
+
+	fn next(&mut self) -> Option<SearchEvent> {
+		self.search.step()
+	}
+}
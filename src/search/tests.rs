@@ -0,0 +1,883 @@
+mod shared_map {
+	use crate::map::{Map, CellType};
+	use crate::runner::Method;
+	use std::str::FromStr;
+	use std::thread;
+
+	const METHODS: &[Method] = &[
+		Method::BreadthFirst,
+		Method::DepthFirst,
+		Method::GreedyBestFirst,
+		Method::AStar,
+		Method::IterativeDeepening,
+		Method::WeightedAStar,
+		Method::Dijkstra,
+		Method::IterativeDeepeningAStar,
+		Method::BidirectionalBreadthFirst,
+		Method::BidirectionalAStar,
+		Method::JumpPoint,
+		Method::AnytimeRepairingAStar,
+	];
+
+	#[test]
+	fn searches_leave_map_untouched() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+		// Targets are never marked as unvisited
+		let pristine = map.count_visited();
+
+		for method in METHODS {
+			let report = method.build(&map, false).search();
+
+			assert!(report.solution.is_some(), "{:?} found no solution", method);
+			assert!(!report.visited.is_empty(), "{:?} reported no visits", method);
+			assert_eq!(map.count_visited(), pristine, "{:?} wrote onto the map", method);
+		}
+	}
+
+	#[test]
+	fn searches_run_in_parallel() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		let map = &map;
+		let lengths: Vec<_> = thread::scope(|s| {
+			METHODS.iter()
+				.map(|method| s.spawn(move || method.build(map, false).search()))
+				.collect::<Vec<_>>()
+				.into_iter()
+				.map(|handle| handle.join().unwrap().solution.map(|x| x.len()))
+				.collect()
+		});
+
+		// BFS, A* and Dijkstra are all optimal on uniform weights
+		assert_eq!(lengths[0], Some(10));
+		assert_eq!(lengths[3], Some(10));
+		assert_eq!(lengths[6], Some(10));
+	}
+}
+
+mod report {
+	use crate::map::{Map, CellType};
+	use crate::runner::Method;
+	use crate::search::MoveWeight;
+	use std::str::FromStr;
+
+	#[test]
+	fn cost_follows_move_weight() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		let uniform = Method::AStar.build(&map, false).search();
+		assert_eq!(uniform.cost, uniform.solution.as_ref().map(|x| x.len()));
+
+		let custom = Method::AStar.build(&map, true).search();
+		let path = custom.solution.expect("no solution found");
+		assert_eq!(custom.cost, Some(map.weights.path_cost(&path)));
+		assert_eq!(custom.target, Some(map.walk(map.initial, &path)));
+	}
+
+	#[test]
+	fn cost_follows_terrain() {
+		// Mud over the whole of the middle row, which the path has to cross
+		let map: Map<CellType> = Map::from_str("[3, 3]\n(0, 0)\n(0, 2)\n(0, 1, 3, 1, 4)").unwrap();
+
+		for method in [Method::BreadthFirst, Method::AStar, Method::Dijkstra, Method::IterativeDeepeningAStar, Method::BidirectionalAStar, Method::JumpPoint] {
+			let report = method.build(&map, false).search();
+			assert_eq!(report.cost, Some(5), "{:?}", method);
+		}
+	}
+
+	#[test]
+	fn expansions_are_recorded_on_request() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		let mut search = Method::BreadthFirst.build(&map, false);
+		assert_eq!(search.search().expanded_cells, None);
+
+		search.record_expansions(true);
+		let report = search.search();
+		let cells = report.expanded_cells.expect("expansions were not recorded");
+
+		assert_eq!(cells.len(), report.expanded);
+		assert_eq!(cells[0], map.initial);
+		assert!(report.generated > report.expanded);
+		assert!(report.peak_frontier > 0);
+		assert!(report.pruned > 0);
+	}
+}
+
+mod arena {
+	use crate::search::arena::Arena;
+	use crate::search::Direction;
+
+	#[test]
+	fn path_is_pieced_back_from_parents() {
+		let mut arena = Arena::default();
+
+		let root = arena.root((1, 1));
+		let right = arena.branch(root, Direction::Right, (2, 1), 2);
+		let _sibling = arena.branch(root, Direction::Up, (1, 0), 4);
+		let down = arena.branch(right, Direction::Down, (2, 2), 1);
+
+		assert_eq!(arena.path(down), vec![Direction::Right, Direction::Down]);
+		assert_eq!(arena.get(down).cost, 3);
+		assert_eq!(arena.ancestry(down).map(|x| x.cursor).collect::<Vec<_>>(), vec![(2, 2), (2, 1), (1, 1)]);
+		assert!(arena.path(root).is_empty());
+	}
+}
+
+mod optimality {
+	use crate::map::{Map, CellType};
+	use crate::runner::{Method, HeuristicKind};
+	use crate::map::movement::{Movement, Corners};
+	use crate::search::{Search, MoveWeight, UniformMoveWeight, CustomMoveWeight, EuclideanMoveWeight};
+	use crate::search::astar::AStarCursor;
+	use crate::search::idastar::IterativeDeepeningAStar;
+	use crate::search::jps::JumpPoint;
+	use crate::utils::heuristics::{Heuristic, ManhattanHeuristic, OctileHeuristic, CustomManhattan};
+	use std::str::FromStr;
+
+	const MAPS: &[&str] = &[
+		include_str!("../map/RobotNav-test.txt"),
+		include_str!("../map/RobotNav-test_MESSY_SPACE.txt"),
+		include_str!("../map/expose_circle.txt"),
+		include_str!("bench/map.txt"),
+	];
+
+	const MOVEMENTS: &[Movement] = &[
+		Movement::FourWay,
+		Movement::EightWay(Corners::Cut),
+		Movement::EightWay(Corners::NoSqueeze),
+		Movement::EightWay(Corners::Avoid),
+		Movement::Knight,
+		Movement::Hex,
+	];
+
+	/// Brute force relaxation of every cell until nothing changes, as a reference to check against
+	fn distances<W: MoveWeight>(map: &Map<CellType>, sources: &[(usize, usize)], weigher: &W) -> Vec<usize> {
+		let mut dist = vec![usize::MAX; map.rows * map.cols];
+		sources.iter().for_each(|x| dist[map.index(*x)] = 0);
+
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for y in 0..map.rows {
+				for x in 0..map.cols {
+					let from = dist[map.index((x, y))];
+					let passable = match map.read_cell((x, y)) {
+						CellType::Wall(_) => false,
+						CellType::Target => sources.contains(&(x, y)),
+						_ => true
+					};
+					if from == usize::MAX || !passable {
+						continue;
+					}
+
+					for (dir, pos) in map.adjacents((x, y)) {
+						let i = map.index(pos);
+						if from + map.move_cost(weigher, dir, pos) < dist[i] {
+							dist[i] = from + map.move_cost(weigher, dir, pos);
+							changed = true;
+						}
+					}
+				}
+			}
+		}
+
+		return dist;
+	}
+
+	fn optimal_cost<W: MoveWeight>(map: &Map<CellType>, weigher: &W) -> Option<usize> {
+		let dist = distances(map, &[map.initial], weigher);
+		map.targets.iter()
+			.map(|x| dist[map.index(*x)])
+			.filter(|x| *x != usize::MAX)
+			.min()
+	}
+
+	/// The cheapest cost going by the weigher that [`Method::build`] picks
+	fn built_optimal_cost(map: &Map<CellType>, var_move_wt: bool) -> Option<usize> {
+		match (var_move_wt, map.movement) {
+			(true, _) => optimal_cost(map, &map.weights),
+			(false, Movement::EightWay(_)) => optimal_cost(map, &EuclideanMoveWeight),
+			_ => optimal_cost(map, &UniformMoveWeight),
+		}
+	}
+
+	#[test]
+	fn optimal_on_sample_maps() {
+		for (i, source) in MAPS.iter().enumerate() {
+			let mut map: Map<CellType> = Map::from_str(source).unwrap();
+
+			for movement in MOVEMENTS {
+				map.movement = *movement;
+
+				for var_move_wt in [false, true] {
+					let expected = built_optimal_cost(&map, var_move_wt);
+
+					for method in [Method::AStar, Method::Dijkstra, Method::IterativeDeepeningAStar, Method::BidirectionalAStar, Method::JumpPoint, Method::AnytimeRepairingAStar] {
+						// IDA* takes an iteration per distinct cost under the bound, which runs into the thousands on the big map with diagonals
+						if method == Method::IterativeDeepeningAStar && i == MAPS.len() - 1 && *movement != Movement::FourWay {
+							continue;
+						}
+
+						let report = method.build(&map, var_move_wt).search();
+						assert_eq!(report.cost, expected, "{:?} on {:?} with variable weights: {}", method, movement, var_move_wt);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn optimal_over_terrain() {
+		for _ in 0..20 {
+			let mut map = Map::random_maze(20, 30, 2);
+			map.scatter_terrain(10, 5);
+
+			for movement in [Movement::FourWay, Movement::EightWay(Corners::Avoid)] {
+				map.movement = movement;
+
+				for var_move_wt in [false, true] {
+					let expected = built_optimal_cost(&map, var_move_wt);
+
+					// IDA* is left out, as the terrain spreads the costs too thin for it to get anywhere in good time
+					for method in [Method::AStar, Method::Dijkstra, Method::BidirectionalAStar, Method::JumpPoint, Method::AnytimeRepairingAStar] {
+						let report = method.build(&map, var_move_wt).search();
+						assert_eq!(report.cost, expected, "{:?} on {:?} with variable weights: {}", method, movement, var_move_wt);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn admissible_heuristics_stay_optimal() {
+		let kinds = [HeuristicKind::Manhattan, HeuristicKind::Euclidean, HeuristicKind::Octile, HeuristicKind::Chebyshev, HeuristicKind::Zero, HeuristicKind::Landmark, HeuristicKind::Exact];
+
+		for _ in 0..5 {
+			let mut map = Map::random_maze(20, 30, 2);
+
+			for movement in MOVEMENTS {
+				map.movement = *movement;
+
+				for var_move_wt in [false, true] {
+					let expected = built_optimal_cost(&map, var_move_wt);
+
+					for kind in kinds.iter().filter(|x| x.admissible(&map, var_move_wt)) {
+						if let Some(expected) = expected {
+							assert!(kind.build(&map, var_move_wt, false).estimate(map.initial) <= expected, "{:?} on {:?}", kind, movement);
+						}
+
+						for method in [Method::AStar, Method::BidirectionalAStar, Method::JumpPoint] {
+							let report = method.build_with(&map, var_move_wt, *kind).search();
+							assert_eq!(report.cost, expected, "{:?} with {:?} on {:?} with variable weights: {}", method, kind, movement, var_move_wt);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn inadmissible_heuristic_is_warned_about() {
+		let mut map: Map<CellType> = Map::from_str(MAPS[0]).unwrap();
+		let mut config = crate::Config {
+			method: "AS".to_owned(),
+			heuristic: Some(HeuristicKind::Manhattan),
+			..Default::default()
+		};
+		assert!(config.heuristic_warning(&map).is_none());
+
+		map.movement = Movement::EightWay(Corners::Avoid);
+		assert!(config.heuristic_warning(&map).is_some());
+
+		// Greedy best-first never promised the cheapest path
+		config.method = "GBFS".to_owned();
+		assert!(config.heuristic_warning(&map).is_none());
+	}
+
+	#[test]
+	fn heuristics_are_only_built_for_the_methods_that_use_them() {
+		let map: Map<CellType> = Map::from_str(MAPS[0]).unwrap();
+		let estimates = |method| {
+			let (forwards, backwards) = HeuristicKind::Exact.build_for(&map, false, method);
+			(forwards.estimate(map.initial) > 0, backwards.estimate(map.targets[0]) > 0)
+		};
+
+		assert_eq!(estimates(Method::BreadthFirst), (false, false));
+		assert_eq!(estimates(Method::Dijkstra), (false, false));
+		assert_eq!(estimates(Method::AStar), (true, false));
+		assert_eq!(estimates(Method::BidirectionalAStar), (true, true));
+	}
+
+	#[test]
+	fn runner_only_gives_ida_star_its_table_when_asked() {
+		let map: Map<CellType> = Map::from_str(MAPS[0]).unwrap();
+		let mut config = crate::Config { method: "IDASTAR".to_owned(), ..Default::default() };
+
+		let light = crate::runner(&map, &config).unwrap();
+		config.cost_table = true;
+		let table = crate::runner(&map, &config).unwrap();
+
+		assert_eq!(light.cost, table.cost);
+		assert!(light.search_nodes > table.search_nodes);
+	}
+
+	#[test]
+	fn optimal_with_runtime_weights() {
+		let weights = [
+			CustomMoveWeight { up: 1, left: 1, down: 1, right: 1 },
+			CustomMoveWeight { up: 1, left: 7, down: 2, right: 3 },
+			CustomMoveWeight { up: 9, left: 1, down: 9, right: 1 },
+			CustomMoveWeight { up: 0, left: 5, down: 5, right: 0 },
+		];
+
+		for _ in 0..10 {
+			let mut map = Map::random_maze(20, 30, 2);
+
+			for weights in weights {
+				map.weights = weights;
+
+				for movement in [Movement::FourWay, Movement::EightWay(Corners::Avoid), Movement::Knight] {
+					map.movement = movement;
+					let expected = optimal_cost(&map, &weights);
+
+					for method in [Method::AStar, Method::Dijkstra, Method::BidirectionalAStar, Method::JumpPoint] {
+						let report = method.build(&map, true).search();
+						assert_eq!(report.cost, expected, "{:?} on {:?} with {}", method, movement, weights);
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn ida_star_optimal_without_cost_table() {
+		// Small enough for IDA* to go down every path
+		for source in &MAPS[..3] {
+			let map: Map<CellType> = Map::from_str(source).unwrap();
+
+			for var_move_wt in [false, true] {
+				let expected = if var_move_wt {
+					optimal_cost(&map, &map.weights)
+				} else {
+					optimal_cost(&map, &UniformMoveWeight)
+				};
+
+				let report = if var_move_wt {
+					IterativeDeepeningAStar::with_weigher(&map, CustomManhattan::init(&map), map.weights).search()
+				} else {
+					IterativeDeepeningAStar::init(&map, ManhattanHeuristic::init(&map)).search()
+				};
+				assert_eq!(report.cost, expected, "with variable weights: {}", var_move_wt);
+			}
+		}
+	}
+
+	#[test]
+	fn bidirectional_meets_on_the_path() {
+		for source in MAPS {
+			let map: Map<CellType> = Map::from_str(source).unwrap();
+			let shortest = optimal_cost(&map, &UniformMoveWeight);
+
+			for var_move_wt in [false, true] {
+				let report = Method::BidirectionalBreadthFirst.build(&map, var_move_wt).search();
+				let path = report.solution.expect("no solution found");
+				assert_eq!(Some(path.len()), shortest, "with variable weights: {}", var_move_wt);
+
+				let meeting = report.meeting.expect("no meeting reported");
+				let mut cursor = map.initial;
+				let mut passed = vec![cursor];
+				for d in &path {
+					cursor = map.adjacent(cursor, *d).unwrap();
+					passed.push(cursor);
+				}
+				assert!(passed.contains(&meeting));
+				assert_eq!(Some(cursor), report.target);
+				assert_eq!(map.read_cell(cursor), &CellType::Target);
+			}
+		}
+	}
+
+	#[test]
+	fn bidirectional_starting_on_a_target() {
+		let map: Map<CellType> = Map::from_str("[3, 3]\n(1, 1)\n(1, 1)").unwrap();
+
+		for method in [Method::BidirectionalBreadthFirst, Method::BidirectionalAStar] {
+			let report = method.build(&map, false).search();
+			assert_eq!(report.solution, Some(vec![]), "{:?}", method);
+			assert_eq!((report.cost, report.target, report.meeting), (Some(0), Some((1, 1)), Some((1, 1))), "{:?}", method);
+		}
+	}
+
+	#[test]
+	fn jump_point_matches_a_star() {
+		for _ in 0..50 {
+			let mut map = Map::random_maze(20, 30, 3);
+
+			for movement in [Movement::FourWay, Movement::EightWay(Corners::Avoid)] {
+				map.movement = movement;
+				let expected = built_optimal_cost(&map, false);
+
+				let report = if movement == Movement::FourWay {
+					JumpPoint::init(&map, ManhattanHeuristic::init(&map)).search()
+				} else {
+					JumpPoint::with_weigher(&map, OctileHeuristic::init(&map), EuclideanMoveWeight).search()
+				};
+				assert_eq!(report.cost, expected, "{:?}", movement);
+
+				// The jumps are filled back in, one move at a time
+				if let Some(path) = report.solution {
+					let mut cursor = map.initial;
+					for d in &path {
+						let (_, next) = *map.adjacents(cursor).iter()
+							.find(|x| x.0 == *d)
+							.expect("not a move that can be made");
+						assert_ne!(map.read_cell(next), &CellType::Wall(false));
+						cursor = next;
+					}
+					assert_eq!(Some(cursor), report.target);
+				}
+			}
+		}
+	}
+
+	/// Exact on the cell next to the initial cell, but nothing anywhere else, which is admissible but not consistent
+	struct Deceptive;
+
+	impl Heuristic for Deceptive {
+		fn estimate(&self, cursor: (usize, usize)) -> usize {
+			if cursor == (1, 1) { 5 } else { 0 }
+		}
+	}
+
+	#[test]
+	fn reopens_with_inconsistent_heuristic() {
+		// The heuristic lures the search around the top to close (2, 1) on the long way first
+		let map: Map<CellType> = Map::from_str("[2, 7]\n(0, 1)\n(6, 1)\n(3, 0, 4, 1)").unwrap();
+
+		let report = graph_search!(&map, Deceptive, AStarCursor<UniformMoveWeight>).search();
+		assert_eq!(report.cost, Some(8));
+
+		let report = graph_search!(&map, Deceptive, AStarCursor<UniformMoveWeight>, cost_table).search();
+		assert_eq!(report.cost, Some(6));
+		assert_eq!(report.cost, optimal_cost(&map, &UniformMoveWeight));
+	}
+}
+
+mod anytime {
+	use crate::map::{Map, CellType};
+	use crate::runner::{Method, HeuristicKind};
+	use crate::search::{Search, UniformMoveWeight};
+	use crate::search::arastar::AnytimeRepairingAStar;
+	use crate::search::steps::SearchEvent;
+	use crate::search::limits::{SearchLimits, Limit};
+	use crate::utils::heuristics::ManhattanHeuristic;
+	use std::str::FromStr;
+
+	fn events(map: &Map<CellType>, limits: SearchLimits) -> Vec<SearchEvent> {
+		let mut search = AnytimeRepairingAStar::with_weigher(map, ManhattanHeuristic::init(map), 5.0, UniformMoveWeight).with_step(1.0);
+		search.set_limits(limits);
+		search.steps().collect()
+	}
+
+	#[test]
+	fn improves_down_to_the_cheapest() {
+		for _ in 0..20 {
+			let mut map = Map::random_maze(20, 30, 2);
+			map.scatter_terrain(10, 5);
+			let optimal = Method::Dijkstra.build(&map, false).search().cost;
+
+			let mut last = (usize::MAX, f64::INFINITY);
+			for event in events(&map, SearchLimits::default()) {
+				let report = match event {
+					SearchEvent::Improved(report) | SearchEvent::Found(report) => report,
+					SearchEvent::Exhausted(_) => {
+						assert_eq!(optimal, None);
+						continue;
+					}
+					_ => continue
+				};
+				let (cost, bound) = (report.cost.unwrap(), report.suboptimality.unwrap());
+
+				assert!(cost <= last.0 && bound <= last.1, "got worse going from {:?} to {:?}", last, (cost, bound));
+				assert!(cost as f64 <= bound * optimal.unwrap() as f64 + 1e-9, "{} is more than {} times {:?}", cost, bound, optimal);
+				last = (cost, bound);
+			}
+
+			if optimal.is_some() {
+				assert_eq!(Some(last.0), optimal);
+				assert_eq!(last.1, 1.0);
+			}
+		}
+	}
+
+	#[test]
+	fn limit_keeps_the_best_so_far() {
+		for _ in 0..20 {
+			let mut map = Map::random_maze(20, 30, 2);
+			map.scatter_terrain(10, 5);
+
+			// Stop a node after the first solution, if it's not the last
+			let first = events(&map, SearchLimits::default()).into_iter().find_map(|x| match x {
+				SearchEvent::Improved(report) => Some(report),
+				_ => None
+			});
+			let first = match first {
+				Some(report) => report,
+				None => continue
+			};
+
+			let last = events(&map, SearchLimits { max_nodes: Some(first.expanded + 1), ..SearchLimits::default() }).pop();
+			match last {
+				Some(SearchEvent::Found(report)) => {
+					assert_eq!(report.cut_off, Some(Limit::Nodes));
+					assert!(report.cost <= first.cost);
+					assert!(report.suboptimality.is_some());
+				}
+				other => panic!("ended with {:?}", other)
+			}
+		}
+	}
+
+	#[test]
+	fn weight_inflates_weighted_a_star() {
+		let map: Map<CellType> = Map::from_str(include_str!("bench/map.txt")).unwrap();
+		let optimal = Method::AStar.build(&map, false).search();
+
+		let heuristic = HeuristicKind::for_map(&map, false);
+		let build = |weight| {
+			let (forwards, backwards) = heuristic.build_both(&map, false);
+			Method::WeightedAStar.build_guided(&map, false, forwards, backwards, weight).search()
+		};
+
+		// Not inflated at all, it's plain A*
+		let plain = build(1.0);
+		assert_eq!(plain.cost, optimal.cost);
+		assert_eq!(plain.expanded, optimal.expanded);
+
+		let inflated = build(3.0);
+		assert!(inflated.expanded < optimal.expanded);
+		assert!(inflated.cost.unwrap() <= optimal.cost.unwrap() * 3);
+	}
+
+	#[test]
+	fn passes_carry_the_frontier_over_without_generating_it_again() {
+		let map: Map<CellType> = Map::from_str(include_str!("bench/map.txt")).unwrap();
+		let events = events(&map, SearchLimits::default());
+		assert!(events.iter().filter(|x| matches!(x, SearchEvent::Improved(_))).count() > 1);
+
+		// A pass starts by expanding what was left over, anything it generates comes from those
+		for pair in events.windows(2) {
+			if let [SearchEvent::Improved(_), next] = pair {
+				assert!(!matches!(next, SearchEvent::Generated(_)), "{:?}", next);
+			}
+		}
+		let generated = events.iter().filter(|x| matches!(x, SearchEvent::Generated(_))).count();
+		match events.last() {
+			Some(SearchEvent::Found(report)) => assert_eq!(report.generated, generated),
+			x => panic!("expected a solution, got {:?}", x)
+		}
+	}
+
+	#[test]
+	fn huge_weights_still_find_a_solution() {
+		let map: Map<CellType> = Map::from_str(include_str!("bench/map.txt")).unwrap();
+		let heuristic = HeuristicKind::for_map(&map, false);
+
+		// The inflated estimates run out of room, and the searches carry on with them stuck at the top
+		for method in vec![Method::WeightedAStar, Method::AnytimeRepairingAStar].into_iter() {
+			let (forwards, backwards) = heuristic.build_both(&map, false);
+			assert!(method.build_guided(&map, false, forwards, backwards, 1e300).search().solution.is_some(), "{:?}", method);
+		}
+	}
+
+	#[test]
+	#[should_panic]
+	fn step_has_to_bring_the_weight_down() {
+		let map: Map<CellType> = Map::from_str(include_str!("bench/map.txt")).unwrap();
+		AnytimeRepairingAStar::with_weigher(&map, ManhattanHeuristic::init(&map), 5.0, UniformMoveWeight).with_step(0.0);
+	}
+}
+
+mod incremental {
+	use crate::map::{Map, CellType};
+	use crate::map::movement::{Movement, Corners};
+	use crate::runner::Method;
+	use crate::search::{Search, Direction, MoveWeight, UniformMoveWeight, EuclideanMoveWeight};
+	use crate::search::incremental::{Replan, LifelongPlanningAStar, DStarLite};
+	use crate::utils::heuristics::{ManhattanHeuristic, OctileHeuristic};
+	use rand::random;
+	use std::str::FromStr;
+
+	/// Walls or clears a handful of random cells, and puts terrain down on a few, keeping off the targets and the agent
+	fn scribble(planner: &mut dyn Replan, agent: (usize, usize)) {
+		let (rows, cols) = (planner.map().rows, planner.map().cols);
+
+		for i in 0..8 {
+			let cell = (random::<usize>() % cols, random::<usize>() % rows);
+			if cell == agent || planner.map().targets.contains(&cell) {
+				continue;
+			}
+
+			if i % 4 == 3 {
+				planner.update_terrain(cell, random::<usize>() % 5 + 1);
+			} else if let CellType::Wall(_) = planner.map().read_cell(cell) {
+				planner.update_cell(cell, CellType::Blank(false));
+			} else {
+				planner.update_cell(cell, CellType::Wall(false));
+			}
+		}
+	}
+
+	/// Checks the plan against a fresh A* over the map as it is now, and that it only goes over open cells to a target
+	fn check_plan<W: MoveWeight>(planner: &dyn Replan, plan: &Option<Vec<Direction>>, weigher: &W) {
+		let map = planner.map();
+		let expected = Method::AStar.build(map, false).search();
+
+		let path = match plan {
+			Some(path) => path,
+			None => return assert_eq!(expected.cost, None, "missed a path")
+		};
+
+		let mut cur = map.initial;
+		for dir in path {
+			assert!(map.clears_corners(cur, *dir), "clipped a wall going {} from {:?}", dir, cur);
+			cur = map.adjacent(cur, *dir).expect("walked off the map");
+			assert!(!matches!(map.read_cell(cur), CellType::Wall(_)), "walked into a wall at {:?}", cur);
+		}
+		assert!(map.targets.contains(&cur), "ended on {:?}", cur);
+		assert_eq!(Some(map.path_cost(weigher, path)), expected.cost);
+	}
+
+	#[test]
+	fn lifelong_a_star_matches_a_fresh_search() {
+		for _ in 0..10 {
+			let map = Map::random_maze(20, 30, 2);
+			let mut planner = LifelongPlanningAStar::init(map.clone(), ManhattanHeuristic::init(&map));
+
+			for _ in 0..10 {
+				let plan = planner.replan(map.initial);
+				check_plan(&planner, &plan, &UniformMoveWeight);
+				scribble(&mut planner, map.initial);
+			}
+		}
+	}
+
+	#[test]
+	fn d_star_lite_matches_a_fresh_search() {
+		for movement in [Movement::FourWay, Movement::EightWay(Corners::Avoid)] {
+			for _ in 0..10 {
+				let mut map = Map::random_maze(20, 30, 2);
+				map.movement = movement;
+
+				let mut agent = map.initial;
+				let mut planner: Box<dyn Replan> = match movement {
+					Movement::FourWay => Box::new(DStarLite::init(map, ManhattanHeuristic::from_initial)),
+					_ => Box::new(DStarLite::with_weigher(map, OctileHeuristic::from_initial, EuclideanMoveWeight)),
+				};
+
+				// Take a few steps along the plan between every round of changes
+				for _ in 0..10 {
+					let plan = planner.replan(agent);
+					match movement {
+						Movement::FourWay => check_plan(&*planner, &plan, &UniformMoveWeight),
+						_ => check_plan(&*planner, &plan, &EuclideanMoveWeight),
+					}
+
+					if let Some(path) = plan {
+						agent = planner.map().walk(agent, &path[..path.len().min(3)]);
+					}
+					scribble(&mut *planner, agent);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn repairs_less_than_planning_afresh() {
+		let map: Map<CellType> = Map::from_str(include_str!("bench/map.txt")).unwrap();
+		let mut planner = DStarLite::init(map.clone(), ManhattanHeuristic::from_initial);
+
+		let first = planner.replan(map.initial);
+		let afresh = planner.expanded();
+
+		// Block the path a few moves ahead
+		let path = first.expect("the map has a path");
+		let blocked = map.walk(map.initial, &path[..path.len() / 2]);
+		planner.update_cell(blocked, CellType::Wall(false));
+
+		let plan = planner.replan(map.initial);
+		check_plan(&planner, &plan, &UniformMoveWeight);
+		assert!(planner.expanded() < afresh, "{} expansions to replan against {} from scratch", planner.expanded(), afresh);
+	}
+}
+
+mod steps {
+	use crate::map::{Map, CellType};
+	use crate::runner::Method;
+	use crate::search::Search;
+	use crate::search::steps::SearchEvent;
+	use std::str::FromStr;
+
+	const METHODS: &[Method] = &[
+		Method::BreadthFirst,
+		Method::DepthFirst,
+		Method::GreedyBestFirst,
+		Method::AStar,
+		Method::IterativeDeepening,
+		Method::WeightedAStar,
+		Method::Dijkstra,
+		Method::IterativeDeepeningAStar,
+		Method::BidirectionalBreadthFirst,
+		Method::BidirectionalAStar,
+		Method::JumpPoint,
+		Method::AnytimeRepairingAStar,
+	];
+
+	#[test]
+	fn stepping_matches_search() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		for method in METHODS {
+			let expected = method.build(&map, false).search();
+
+			let mut search = method.build(&map, false);
+			let events: Vec<_> = search.steps().collect();
+			let expanded = events.iter().filter(|x| matches!(x, SearchEvent::Expanded(_))).count();
+
+			match events.last() {
+				Some(SearchEvent::Found(report)) => {
+					assert_eq!(report.solution, expected.solution, "{:?} stepped to another solution", method);
+					assert_eq!(report.expanded, expanded, "{:?} missed expansion events", method);
+				}
+				other => panic!("{:?} ended with {:?}", method, other)
+			}
+			assert!(search.step().is_none(), "{:?} kept going after finishing", method);
+		}
+	}
+
+	#[test]
+	fn advance_is_capped_and_resumable() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+		let expected = Method::BreadthFirst.build(&map, false).search();
+
+		let mut search = Method::BreadthFirst.build(&map, false);
+		let mut rounds = 0;
+		let report = loop {
+			rounds += 1;
+			if let Some(report) = search.advance(5) {
+				break report;
+			}
+		};
+
+		assert!(rounds > 1);
+		assert!(report.expanded <= rounds * 5);
+		assert_eq!(report.solution, expected.solution);
+		assert_eq!(report.expanded, expected.expanded);
+	}
+}
+
+mod limits {
+	use crate::map::{Map, CellType};
+	use crate::runner::Method;
+	use crate::search::limits::{SearchLimits, Limit};
+	use std::str::FromStr;
+	use std::sync::Arc;
+	use std::sync::atomic::AtomicBool;
+
+	const METHODS: &[Method] = &[
+		Method::BreadthFirst,
+		Method::DepthFirst,
+		Method::GreedyBestFirst,
+		Method::AStar,
+		Method::IterativeDeepening,
+		Method::WeightedAStar,
+		Method::Dijkstra,
+		Method::IterativeDeepeningAStar,
+		Method::BidirectionalBreadthFirst,
+		Method::BidirectionalAStar,
+		Method::JumpPoint,
+		Method::AnytimeRepairingAStar,
+	];
+
+	fn run(map: &Map<CellType>, method: Method, limits: SearchLimits) -> crate::SearchReport {
+		let mut search = method.build(map, false);
+		search.set_limits(limits);
+		search.search()
+	}
+
+	#[test]
+	fn node_limit_cuts_off() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		// Jump Point Search gets there in fewer than 5 jumps
+		for method in METHODS.iter().filter(|x| **x != Method::JumpPoint) {
+			let report = run(&map, *method, SearchLimits { max_nodes: Some(5), ..SearchLimits::default() });
+
+			assert_eq!(report.solution, None, "{:?}", method);
+			assert_eq!(report.cut_off, Some(Limit::Nodes), "{:?}", method);
+			assert_eq!(report.expanded, 5, "{:?}", method);
+		}
+
+		let report = run(&map, Method::JumpPoint, SearchLimits { max_nodes: Some(2), ..SearchLimits::default() });
+		assert_eq!(report.cut_off, Some(Limit::Nodes));
+		assert_eq!(report.expanded, 2);
+	}
+
+	#[test]
+	fn depth_limit_bounds_the_solution() {
+		// The shortest solution is 10 moves long
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		for method in METHODS {
+			let report = run(&map, *method, SearchLimits { max_depth: Some(9), ..SearchLimits::default() });
+			assert_eq!(report.cut_off, Some(Limit::Depth), "{:?}", method);
+
+			let report = run(&map, *method, SearchLimits { max_depth: Some(10), ..SearchLimits::default() });
+			if let Some(path) = &report.solution {
+				assert!(path.len() <= 10, "{:?} went past the depth limit", method);
+			} else {
+				assert_eq!(report.cut_off, Some(Limit::Depth), "{:?}", method);
+			}
+		}
+
+		for method in [Method::BreadthFirst, Method::AStar, Method::Dijkstra] {
+			let report = run(&map, method, SearchLimits { max_depth: Some(10), ..SearchLimits::default() });
+			assert_eq!(report.solution.map(|x| x.len()), Some(10), "{:?}", method);
+		}
+	}
+
+	#[test]
+	fn cost_limit_cuts_off() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		let report = run(&map, Method::Dijkstra, SearchLimits { max_cost: Some(9), ..SearchLimits::default() });
+		assert_eq!(report.cut_off, Some(Limit::Cost));
+	}
+
+	#[test]
+	fn cancelled_before_starting() {
+		let map: Map<CellType> = Map::from_str(include_str!("../map/RobotNav-test.txt")).unwrap();
+
+		for method in METHODS {
+			let cancel = Arc::new(AtomicBool::new(true));
+			let report = run(&map, *method, SearchLimits { cancel: Some(cancel), ..SearchLimits::default() });
+
+			// The depth-first searches step onto the initial cell as they start
+			assert_eq!(report.cut_off, Some(Limit::Cancelled), "{:?}", method);
+			assert!(report.expanded <= 1, "{:?}", method);
+		}
+	}
+
+	#[test]
+	fn unreachable_target_is_not_a_cut_off() {
+		let map: Map<CellType> = Map::from_str("[3, 3]\n(0, 0)\n(2, 2)\n(0, 1, 3, 1)").unwrap();
+
+		for method in METHODS {
+			let report = run(&map, *method, SearchLimits { max_depth: Some(100), max_nodes: Some(100), ..SearchLimits::default() });
+
+			assert_eq!(report.solution, None, "{:?}", method);
+			assert_eq!(report.cut_off, None, "{:?}", method);
+		}
+	}
+}
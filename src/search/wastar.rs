@@ -1,56 +1,33 @@
-use crate::search::{Direction, Cursor, MoveWeight};
-use crate::utils::queue::sorted::Weighted;
-
-const WEIGHT_MODIFIER: usize = 2;
-
-#[derive(Default, Debug)]
-pub struct WeightedASCursor<W> {
-	heuristic_weight: usize,
-	path: Vec<Direction>,
-	cursor: (usize, usize),
-	weigher: W,
+use crate::utils::heuristics::Heuristic;
+
+/// How much the heuristic is inflated by when it's not up to the caller
+pub const DEFAULT_WEIGHT: f64 = 2.0;
+
+/// Weighted A* is A* with the heuristic inflated by a weight, this does the inflating
+///
+/// Weights over 1 trust the heuristic more than the cost so far, which gets to a target with fewer expansions,
+/// and the path costs no more than the weight times the cheapest one as long as the heuristic never overestimates.
+/// Estimates are rounded down.
+pub struct Inflated<H> {
+	heuristic: H,
+	weight: f64,
 }
 
-impl<W> Cursor for WeightedASCursor<W> where
-	W: MoveWeight {
-	type DirectionWeigher = W;
-
-	fn path(&self) -> &Vec<Direction> {
-		&self.path
-	}
-
-	fn path_mut(&mut self) -> &mut Vec<Direction> {
-		&mut self.path
-	}
-
-	fn cursor(&self) -> &(usize, usize) {
-		&self.cursor
-	}
-
-	fn cursor_mut(&mut self) -> &mut (usize, usize) {
-		&mut self.cursor
-	}
-
-	fn into_path(self) -> Vec<Direction> {
-		self.path
+impl<H> Inflated<H> where
+	H: Heuristic {
+	/// Panics if the weight isn't a number from 1 up
+	pub fn init(heuristic: H, weight: f64) -> Self {
+		assert!(weight.is_finite() && weight >= 1.0, "the heuristic can only be inflated by a number from 1 up, not {}", weight);
+		Inflated {
+			heuristic,
+			weight,
+		}
 	}
 }
 
-impl<W> Weighted for WeightedASCursor<W> where
-	W: MoveWeight {
-	/// Weighted AStar weight = travelled weight + (weighing function * heuristic weight)
-	/// In this case, the weighing function is also heuristic weight
-	fn weigh(&self) -> usize {
-		self.heuristic_weight * WEIGHT_MODIFIER + self.path.iter()
-			.map(|d| self.weigher.weigh(d))
-			.fold(0, |fold, add| fold + add)
-	}
-
-	fn heuristic_weight(&mut self) -> &mut usize {
-		&mut self.heuristic_weight
-	}
-
-	fn direction(&self) -> Option<Direction> {
-		self.path.last().map(|x| *x)
+impl<H> Heuristic for Inflated<H> where
+	H: Heuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		(self.heuristic.estimate(cursor) as f64 * self.weight) as usize
 	}
 }
@@ -0,0 +1,259 @@
+#[cfg(test)]
+mod tests;
+
+use crate::map::{Map, CellType};
+use crate::runner::{Method, HeuristicKind, select_landmarks, landmark_heuristics};
+use crate::search::limits::SearchLimits;
+use crate::search::wastar::DEFAULT_WEIGHT;
+use crate::utils::landmarks::Landmarks;
+use crate::utils::visited::Visited;
+use crate::SearchReport;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Most targets a tour can have for its visiting order to be solved exactly,
+/// any more than that and the order is approximated with nearest neighbour and 2-opt
+pub const EXACT_LIMIT: usize = 10;
+
+/// A route from the initial cell that goes through every target on the map
+pub struct Tour {
+	/// The targets in the order they're visited
+	pub order: Vec<(usize, usize)>,
+	/// Report of the whole route, with the statistics of every search run to piece it together
+	pub report: SearchReport,
+}
+
+/// Plans a route that visits every target of the map, going through them in the cheapest order it can find
+///
+/// The path between every pair of stops, the stops being the initial cell and the targets, is found with `method`,
+/// so the legs are only as good as the method; the `limits` apply to each of these searches on their own.
+/// The visiting order is then solved exactly for up to [`EXACT_LIMIT`] targets, and approximated for more.
+///
+/// `heuristic` guides the informed methods, or leave it to [`HeuristicKind::for_map`] with [`None`],
+/// and `weight` inflates it for weighted A* and ARA*, or leave it to [`DEFAULT_WEIGHT`] with [`None`].
+/// The tables of [`HeuristicKind::Landmark`] don't depend on the stops, so they're worked out once for every leg,
+/// or `landmarks` that are already worked out for the map are used instead
+pub fn plan_tour(map: &Map<CellType>, method: Method, var_move_wt: bool, heuristic: Option<HeuristicKind>, landmarks: Option<Arc<Landmarks>>, weight: Option<f64>, limits: &SearchLimits) -> Tour {
+	let started = Instant::now();
+	let heuristic = heuristic.unwrap_or_else(|| HeuristicKind::for_map(map, var_move_wt));
+	let weight = weight.unwrap_or(DEFAULT_WEIGHT);
+	let landmarks = match landmarks {
+		_ if heuristic != HeuristicKind::Landmark || !method.is_informed() => None,
+		Some(x) => Some(x),
+		None => Some(Arc::new(select_landmarks(map, var_move_wt))),
+	};
+
+	let mut stops = vec![map.initial];
+	for target in &map.targets {
+		if !stops.contains(target) {
+			stops.push(*target);
+		}
+	}
+
+	let mut report = SearchReport {
+		search_nodes: 0,
+		solution: None,
+		cost: None,
+		target: None,
+		visited: Visited::init(map),
+		generated: 0,
+		expanded: 0,
+		pruned: 0,
+		peak_frontier: 0,
+		elapsed: Default::default(),
+		expanded_cells: None,
+		cut_off: None,
+		meeting: None,
+		suboptimality: None,
+	};
+
+	// Unreachable legs are left at usize::MAX
+	let mut costs = vec![vec![usize::MAX; stops.len()]; stops.len()];
+	let mut paths = vec![vec![None; stops.len()]; stops.len()];
+
+	for (i, from) in stops.iter().enumerate() {
+		// Nothing leads back to the initial cell since the tour doesn't return
+		for (j, to) in stops.iter().enumerate().skip(1) {
+			if i == j {
+				continue;
+			}
+
+			let leg = map.between(*from, *to);
+			let (forwards, backwards) = match &landmarks {
+				Some(x) => landmark_heuristics(&leg, x.clone()),
+				None => heuristic.build_for(&leg, var_move_wt, method),
+			};
+			let mut search = method.build_guided(&leg, var_move_wt, forwards, backwards, weight);
+			search.set_limits(limits.clone());
+			let result = search.search();
+
+			report.search_nodes += result.search_nodes;
+			report.generated += result.generated;
+			report.expanded += result.expanded;
+			report.pruned += result.pruned;
+			report.peak_frontier = report.peak_frontier.max(result.peak_frontier);
+			report.visited.union(&result.visited);
+			if let Some(limit) = result.cut_off {
+				report.cut_off.get_or_insert(limit);
+			}
+
+			if let (Some(cost), Some(path)) = (result.cost, result.solution) {
+				costs[i][j] = cost;
+				paths[i][j] = Some(path);
+			}
+		}
+	}
+
+	let order = if stops.len() - 1 <= EXACT_LIMIT {
+		exact_order(&costs)
+	} else {
+		two_opt(&costs, nearest_neighbour(&costs))
+	};
+
+	let cost = route_cost(&costs, &order);
+	if cost != usize::MAX {
+		let mut solution = vec![];
+		let mut from = 0;
+		for to in &order {
+			solution.extend(paths[from][*to].iter().flatten());
+			from = *to;
+		}
+
+		report.solution = Some(solution);
+		report.cost = Some(cost);
+		report.target = Some(stops[from]);
+		// Cut off legs don't matter if the tour could go around them
+		report.cut_off = None;
+	}
+	report.elapsed = started.elapsed();
+
+	Tour {
+		order: order.into_iter().map(|x| stops[x]).collect(),
+		report,
+	}
+}
+
+/// Cost of starting on stop 0 and going through the stops in `order`, [`usize::MAX`] if any leg is unreachable
+fn route_cost(costs: &[Vec<usize>], order: &[usize]) -> usize {
+	let mut from = 0;
+	let mut total = 0usize;
+	for to in order {
+		total = total.saturating_add(costs[from][*to]);
+		from = *to;
+	}
+	return total;
+}
+
+/// Held-Karp dynamic programming over the subsets of stops, which is exact but exponential
+///
+/// The stops other than stop 0 are numbered from 0 in the subset masks
+fn exact_order(costs: &[Vec<usize>]) -> Vec<usize> {
+	let count = costs.len() - 1;
+	if count == 0 {
+		return vec![];
+	}
+
+	let full = (1 << count) - 1;
+	// Cheapest cost of going through the stops in the mask, ending on the stop, and the stop before that
+	let mut best = vec![vec![usize::MAX; count]; full + 1];
+	let mut prev = vec![vec![usize::MAX; count]; full + 1];
+
+	for stop in 0..count {
+		best[1 << stop][stop] = costs[0][stop + 1];
+	}
+
+	for mask in 1..=full {
+		for last in 0..count {
+			if mask & (1 << last) == 0 || best[mask][last] == usize::MAX {
+				continue;
+			}
+
+			for next in 0..count {
+				if mask & (1 << next) != 0 {
+					continue;
+				}
+
+				let cost = best[mask][last].saturating_add(costs[last + 1][next + 1]);
+				let extended = mask | (1 << next);
+				if cost < best[extended][next] {
+					best[extended][next] = cost;
+					prev[extended][next] = last;
+				}
+			}
+		}
+	}
+
+	let mut last = (0..count)
+		.min_by_key(|x| best[full][*x])
+		.expect("there's at least one stop");
+	if best[full][last] == usize::MAX {
+		// Some target can't be reached at all, so any order will do
+		return (1..=count).collect();
+	}
+
+	// Trace the route back from the last stop
+	let mut order = vec![];
+	let mut mask = full;
+	loop {
+		order.push(last + 1);
+
+		let before = prev[mask][last];
+		mask ^= 1 << last;
+		if mask == 0 {
+			break;
+		}
+		last = before;
+	}
+	order.reverse();
+
+	return order;
+}
+
+/// Always heads to the cheapest stop yet to be visited
+fn nearest_neighbour(costs: &[Vec<usize>]) -> Vec<usize> {
+	let mut remaining: Vec<usize> = (1..costs.len()).collect();
+	let mut order = vec![];
+	let mut from = 0;
+
+	while !remaining.is_empty() {
+		let (i, next) = remaining.iter()
+			.copied()
+			.enumerate()
+			.min_by_key(|(_, x)| costs[from][*x])
+			.expect("checked to be non-empty");
+
+		remaining.remove(i);
+		order.push(next);
+		from = next;
+	}
+
+	return order;
+}
+
+/// Improves on the order by reversing stretches of it, until no reversal makes the route any cheaper
+///
+/// The whole route is costed for every reversal, since the legs may cost differently in either direction
+fn two_opt(costs: &[Vec<usize>], mut order: Vec<usize>) -> Vec<usize> {
+	let mut best = route_cost(costs, &order);
+	let mut improved = true;
+
+	while improved {
+		improved = false;
+
+		for i in 0..order.len() {
+			for j in i + 1..order.len() {
+				order[i..=j].reverse();
+
+				let cost = route_cost(costs, &order);
+				if cost < best {
+					best = cost;
+					improved = true;
+				} else {
+					order[i..=j].reverse();
+				}
+			}
+		}
+	}
+
+	return order;
+}
@@ -0,0 +1,119 @@
+mod order {
+	use crate::tour::{exact_order, nearest_neighbour, two_opt, route_cost};
+	use rand::random;
+
+	/// Every order there is, for checking against
+	fn permutations(stops: Vec<usize>) -> Vec<Vec<usize>> {
+		if stops.is_empty() {
+			return vec![vec![]];
+		}
+
+		let mut all = vec![];
+		for (i, first) in stops.iter().enumerate() {
+			let mut rest = stops.clone();
+			rest.remove(i);
+
+			for mut tail in permutations(rest) {
+				tail.insert(0, *first);
+				all.push(tail);
+			}
+		}
+		return all;
+	}
+
+	#[test]
+	fn exact_matches_brute_force() {
+		for _ in 0..20 {
+			// Asymmetric costs, like those under the custom move weights
+			let costs: Vec<Vec<usize>> = (0..7)
+				.map(|_| (0..7).map(|_| random::<usize>() % 50 + 1).collect())
+				.collect();
+
+			let brute = permutations((1..7).collect()).iter()
+				.map(|x| route_cost(&costs, x))
+				.min();
+			let exact = exact_order(&costs);
+
+			assert_eq!(exact.len(), 6);
+			assert_eq!(Some(route_cost(&costs, &exact)), brute);
+
+			let approx = two_opt(&costs, nearest_neighbour(&costs));
+			assert!(route_cost(&costs, &approx) >= route_cost(&costs, &exact));
+			assert!(route_cost(&costs, &approx) <= route_cost(&costs, &nearest_neighbour(&costs)));
+		}
+	}
+}
+
+mod tour {
+	use crate::map::{Map, CellType};
+	use crate::runner::{Method, HeuristicKind};
+	use crate::search::UniformMoveWeight;
+	use crate::search::limits::SearchLimits;
+	use crate::tour::{plan_tour, EXACT_LIMIT};
+	use crate::utils::landmarks::{Landmarks, Selection};
+	use std::str::FromStr;
+	use std::sync::Arc;
+
+	#[test]
+	fn goes_the_short_way_first() {
+		// Heading right to (6, 0) first leaves less to walk back than heading left to (0, 0) first
+		let map: Map<CellType> = Map::from_str("[1, 7]\n(4, 0)\n(0, 0) | (6, 0)\n").unwrap();
+		let tour = plan_tour(&map, Method::AStar, false, None, None, None, &SearchLimits::default());
+
+		assert_eq!(tour.order, vec![(6, 0), (0, 0)]);
+		assert_eq!(tour.report.cost, Some(8));
+		assert_eq!(tour.report.solution.map(|x| x.len()), Some(8));
+		assert_eq!(tour.report.target, Some((0, 0)));
+	}
+
+	#[test]
+	fn visits_every_target() {
+		let mut source = String::from("[8, 8]\n(0, 0)\n");
+		let targets: Vec<String> = (0..EXACT_LIMIT + 4)
+			.map(|i| format!("({}, {})", (i * 5) % 8, (i * 3 + 1) % 8))
+			.collect();
+		source += &targets.join(" | ");
+		source += "\n(3, 3, 2, 2)";
+		let map: Map<CellType> = Map::from_str(&source).unwrap();
+
+		for method in [Method::AStar, Method::BreadthFirst] {
+			let tour = plan_tour(&map, method, false, None, None, None, &SearchLimits::default());
+			let path = tour.report.solution.expect("no tour found");
+
+			let mut cursor = map.initial;
+			let mut passed = vec![cursor];
+			for d in &path {
+				cursor = map.adjacent(cursor, *d).unwrap();
+				assert!(!matches!(map.read_cell(cursor), CellType::Wall(_)), "{:?} walked into a wall", method);
+				passed.push(cursor);
+			}
+
+			assert_eq!(tour.report.cost, Some(path.len()));
+			for target in &map.targets {
+				assert!(passed.contains(target), "{:?} missed {:?}", method, target);
+			}
+		}
+	}
+
+	#[test]
+	fn landmark_tables_are_shared_across_legs() {
+		let map = Map::random_maze(20, 20, 5);
+		let limits = SearchLimits::default();
+		let expected = plan_tour(&map, Method::AStar, false, None, None, None, &limits).report.cost;
+
+		let landmarks = Arc::new(Landmarks::select(&map, &UniformMoveWeight, 4, Selection::Corners));
+		for landmarks in [None, Some(landmarks)] {
+			let tour = plan_tour(&map, Method::AStar, false, Some(HeuristicKind::Landmark), landmarks, None, &limits);
+			assert_eq!(tour.report.cost, expected);
+		}
+	}
+
+	#[test]
+	fn unreachable_target_means_no_tour() {
+		let map: Map<CellType> = Map::from_str("[3, 3]\n(0, 0)\n(1, 0) | (2, 2)\n(0, 1, 3, 1)").unwrap();
+		let tour = plan_tour(&map, Method::AStar, false, None, None, None, &SearchLimits::default());
+
+		assert_eq!(tour.report.solution, None);
+		assert_eq!(tour.report.cut_off, None);
+	}
+}
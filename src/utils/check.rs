@@ -0,0 +1,99 @@
+use crate::map::{Map, CellType};
+use crate::search::{Direction, MoveWeight};
+use crate::utils::distances::DistanceField;
+use crate::utils::heuristics::Heuristic;
+use std::fmt::{self, Display};
+
+/// A cell where the heuristic guesses more than it costs to get to the nearest target
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Overestimate {
+	pub cell: (usize, usize),
+	pub estimate: usize,
+	pub cost: usize,
+}
+
+/// A move over which the estimate drops by more than the move costs, as in h(n) > c(n, n') + h(n')
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Inconsistency {
+	pub from: (usize, usize),
+	pub direction: Direction,
+	pub to: (usize, usize),
+	/// Estimates at either end of the move
+	pub estimates: (usize, usize),
+	pub cost: usize,
+}
+
+/// Everywhere a heuristic goes wrong on a map, see [`check_heuristic`]
+#[derive(Clone, Debug, Default)]
+pub struct HeuristicCheck {
+	pub overestimates: Vec<Overestimate>,
+	pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl HeuristicCheck {
+	/// Never overestimates, so A* finds the cheapest path
+	pub fn admissible(&self) -> bool {
+		self.overestimates.is_empty()
+	}
+
+	/// Never drops by more than a move costs, so A* never has to expand a cell twice
+	pub fn consistent(&self) -> bool {
+		self.inconsistencies.is_empty()
+	}
+}
+
+/// Goes over every open cell of the map and every move out of them, comparing the heuristic to the true costs under the weigher
+///
+/// Cells that can't get to a target can't be overestimated, and no moves are made out of the targets, since the searches stop there
+pub fn check_heuristic<H, W>(map: &Map<CellType>, heuristic: &H, weigher: &W) -> HeuristicCheck where
+	H: Heuristic + ?Sized,
+	W: MoveWeight {
+	let field = DistanceField::init(map, weigher);
+	let mut check = HeuristicCheck::default();
+
+	for y in 0..map.rows {
+		for x in 0..map.cols {
+			let cell = (x, y);
+			if let CellType::Wall(_) = map.read_cell(cell) {
+				continue;
+			}
+
+			let estimate = heuristic.estimate(cell);
+			if let Some(cost) = field.get(cell).filter(|cost| estimate > *cost) {
+				check.overestimates.push(Overestimate { cell, estimate, cost });
+			}
+
+			if let CellType::Target = map.read_cell(cell) {
+				continue;
+			}
+			for (direction, to) in map.adjacents(cell) {
+				if let CellType::Wall(_) = map.read_cell(to) {
+					continue;
+				}
+
+				let cost = map.move_cost(weigher, direction, to);
+				let next = heuristic.estimate(to);
+				if estimate > cost + next {
+					check.inconsistencies.push(Inconsistency { from: cell, direction, to, estimates: (estimate, next), cost });
+				}
+			}
+		}
+	}
+
+	return check;
+}
+
+/// A line for each time the heuristic went wrong, then whether it's admissible and consistent
+impl Display for HeuristicCheck {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for x in &self.overestimates {
+			writeln!(f, "Overestimates at {:?}: {} > {}", x.cell, x.estimate, x.cost)?;
+		}
+		for x in &self.inconsistencies {
+			writeln!(f, "Inconsistent going {} from {:?} to {:?}: {} > {} + {}", x.direction, x.from, x.to, x.estimates.0, x.cost, x.estimates.1)?;
+		}
+
+		writeln!(f, "Admissible: {}", if self.admissible() { "yes" } else { "no" })?;
+		write!(f, "Consistent: {}", if self.consistent() { "yes" } else { "no" })
+	}
+}
@@ -0,0 +1,152 @@
+use crate::map::{Map, CellType};
+use crate::search::{Direction, MoveWeight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// Cheapest cost of getting from any of the `sources` to every cell, keyed by [`Map::index`]
+///
+/// Plain Dijkstra over the whole map, targets included, with [`usize::MAX`] for the cells that can't be reached and the walls
+pub fn from_sources<W: MoveWeight>(map: &Map<CellType>, sources: &[(usize, usize)], weigher: &W) -> Vec<usize> {
+	return dijkstra(map, sources, |cur| {
+		map.adjacents(cur).into_iter()
+			.map(|(dir, next)| (next, map.move_cost(weigher, dir, next)))
+			.collect()
+	});
+}
+
+/// Cheapest cost of getting from every cell to the nearest of the `sinks`, keyed by [`Map::index`]
+///
+/// Dijkstra run backwards from the sinks, so moves are weighed the way they're made going forwards
+pub fn to_sinks<W: MoveWeight>(map: &Map<CellType>, sinks: &[(usize, usize)], weigher: &W) -> Vec<usize> {
+	return dijkstra(map, sinks, |cur| {
+		map.predecessors(cur).into_iter()
+			.map(|(dir, prev)| (prev, map.move_cost(weigher, dir, cur)))
+			.collect()
+	});
+}
+
+/// Dijkstra from the `sources`, with `edges` handing out the cells next to one and the cost of the move between them
+fn dijkstra<F>(map: &Map<CellType>, sources: &[(usize, usize)], edges: F) -> Vec<usize> where
+	F: Fn((usize, usize)) -> Vec<((usize, usize), usize)> {
+	let mut dist = vec![usize::MAX; map.rows * map.cols];
+	let mut queue = BinaryHeap::new();
+
+	for source in sources {
+		dist[map.index(*source)] = 0;
+		queue.push(Reverse((0, *source)));
+	}
+
+	while let Some(Reverse((cost, cur))) = queue.pop() {
+		// Stale entry, the cell has since been reached more cheaply
+		if cost > dist[map.index(cur)] {
+			continue;
+		}
+
+		for (next, weight) in edges(cur) {
+			if let CellType::Wall(_) = map.read_cell(next) {
+				continue;
+			}
+
+			let i = map.index(next);
+			if cost + weight < dist[i] {
+				dist[i] = cost + weight;
+				queue.push(Reverse((dist[i], next)));
+			}
+		}
+	}
+
+	return dist;
+}
+
+/// The exact cost of getting from every cell to the nearest target, or from the initial cell to every cell
+///
+/// Works as a heuristic that's never off, so A* only expands the cells of a cheapest path,
+/// and can be followed downhill from any cell with [`DistanceField::next_move`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DistanceField {
+	pub rows: usize,
+	pub cols: usize,
+	/// Keyed by [`Map::index`], [`usize::MAX`] for the walls and cells that are cut off
+	costs: Vec<usize>,
+	/// Costs from the initial cell rather than to the targets
+	backwards: bool,
+	/// The estimate for cells that can't get anywhere, more than any path on the map costs so they're never expanded,
+	/// but not so much that adding a cost to it overflows
+	pub(crate) cut_off: usize,
+}
+
+impl DistanceField {
+	/// Costs of getting to the nearest target, by running Dijkstra backwards from all of them at once
+	pub fn init<W: MoveWeight>(map: &Map<CellType>, weigher: &W) -> DistanceField {
+		DistanceField::of(map, to_sinks(map, &map.targets, weigher), false)
+	}
+
+	/// Costs of getting from the initial cell instead, for searching backwards from the targets
+	pub fn from_initial<W: MoveWeight>(map: &Map<CellType>, weigher: &W) -> DistanceField {
+		DistanceField::of(map, from_sources(map, &[map.initial], weigher), true)
+	}
+
+	fn of(map: &Map<CellType>, costs: Vec<usize>, backwards: bool) -> DistanceField {
+		let cut_off = costs.iter().filter(|x| **x != usize::MAX).max().map_or(0, |x| x + 1);
+
+		DistanceField {
+			rows: map.rows,
+			cols: map.cols,
+			costs,
+			backwards,
+			cut_off,
+		}
+	}
+
+	/// The cost at the cell, or [`None`] for walls and cells that are cut off
+	pub fn get(&self, (x, y): (usize, usize)) -> Option<usize> {
+		Some(self.costs[x + y * self.cols]).filter(|x| *x != usize::MAX)
+	}
+
+	/// All the costs, keyed by [`Map::index`]
+	pub fn costs(&self) -> &[usize] {
+		&self.costs
+	}
+
+	/// A move from the cell onto a cheapest path to the nearest target, along with the cell it lands on
+	///
+	/// [`None`] on a target, or where there's no getting to one. Only makes sense for a field out of [`DistanceField::init`].
+	pub fn next_move<W: MoveWeight>(&self, map: &Map<CellType>, weigher: &W, cur: (usize, usize)) -> Option<(Direction, (usize, usize))> {
+		debug_assert!(!self.backwards, "the field goes from the initial cell");
+		let here = self.get(cur).filter(|x| *x > 0)?;
+
+		map.adjacents(cur).into_iter()
+			.filter(|(_, next)| !matches!(map.read_cell(*next), CellType::Wall(_)))
+			.find(|(dir, next)| self.get(*next).map(|x| x + map.move_cost(weigher, *dir, *next)) == Some(here))
+	}
+
+	/// Serializes the field and writes it to a file
+	pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+		let mut handle = OpenOptions::new()
+			.create_new(true)
+			.write(true)
+			.open(path)?;
+
+		write!(handle, "{}", self)?;
+
+		Ok(())
+	}
+}
+
+/// One row of the map per line, with `-` for the walls and the cells that are cut off
+impl Display for DistanceField {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for row in self.costs.chunks(self.cols) {
+			writeln!(f, "{}", row.iter()
+				.map(|x| if *x == usize::MAX { "-".to_owned() } else { x.to_string() })
+				.collect::<Vec<String>>()
+				.join(" "))?;
+		}
+
+		Ok(())
+	}
+}
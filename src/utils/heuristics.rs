@@ -1,13 +1,24 @@
 use crate::map::{CellType, Map};
-use crate::search::{CustomMoveWeight, Direction};
+use crate::search::{CustomMoveWeight, EuclideanMoveWeight};
+use crate::utils::landmarks::Landmarks;
+use crate::utils::distances::DistanceField;
 use std::ops::Mul;
+use std::sync::Arc;
 
 /// Trait for a basic heuristic function, universal adapters are good
 pub trait Heuristic {
 	fn estimate(&self, cursor: (usize, usize)) -> usize;
 }
 
-/// A heuristic adapter that always returns 0 for compatibility with the existing code
+/// Lets heuristics be picked at runtime
+impl<H> Heuristic for Box<H> where
+	H: Heuristic + ?Sized {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		(**self).estimate(cursor)
+	}
+}
+
+/// A heuristic adapter that always returns 0 for compatibility with the existing code, which turns A* into Dijkstra
 #[derive(Default)]
 pub struct DefaultHeuristic;
 
@@ -30,6 +41,13 @@ impl ManhattanHeuristic {
 			targets: map.targets.to_owned(),
 		}
 	}
+
+	/// Estimates the distance from the initial cell instead, for searching backwards from the targets
+	pub fn from_initial(map: &Map<CellType>) -> Self {
+		ManhattanHeuristic {
+			targets: vec![map.initial],
+		}
+	}
 }
 
 impl Heuristic for ManhattanHeuristic {
@@ -44,16 +62,201 @@ impl Heuristic for ManhattanHeuristic {
 	}
 }
 
-/// Manhattan-distance heuristic function, but adjusted such that it takes the variable-distance effects of [`VariableMoveWeight`]
+/// Octile-distance heuristic function, the cost of going diagonally as far as possible and then straight the rest of the way,
+/// weighed in the same units as [`EuclideanMoveWeight`] for eight-way movement unless given other weights
+#[derive(Default)]
+pub struct OctileHeuristic {
+	targets: Vec<(usize, usize)>,
+	straight: usize,
+	diagonal: usize,
+}
+
+impl OctileHeuristic {
+	pub fn init(map: &Map<CellType>) -> Self {
+		OctileHeuristic {
+			targets: map.targets.to_owned(),
+			straight: EuclideanMoveWeight::STRAIGHT,
+			diagonal: EuclideanMoveWeight::DIAGONAL,
+		}
+	}
+
+	/// Estimates the distance from the initial cell instead, for searching backwards from the targets
+	pub fn from_initial(map: &Map<CellType>) -> Self {
+		OctileHeuristic {
+			targets: vec![map.initial],
+			straight: EuclideanMoveWeight::STRAIGHT,
+			diagonal: EuclideanMoveWeight::DIAGONAL,
+		}
+	}
+
+	/// Weigh the straight and the diagonal moves differently, which should be the cheapest of each to stay admissible
+	pub fn with_weights(mut self, straight: usize, diagonal: usize) -> Self {
+		self.straight = straight;
+		self.diagonal = diagonal;
+		self
+	}
+}
+
+impl Heuristic for OctileHeuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.targets.iter()
+			.map(|(ref x, ref y)| {
+				let dx = cursor.0.max(*x) - cursor.0.min(*x);
+				let dy = cursor.1.max(*y) - cursor.1.min(*y);
+
+				self.diagonal * dx.min(dy) + self.straight * (dx.max(dy) - dx.min(dy))
+			})
+			.min() // Lead to the closer target
+			.expect("no targets found")
+	}
+}
+
+/// Straight-line distance heuristic function, scaled by the weight of a move along a straight line one cell long
+///
+/// Rounded down, so it never overestimates as long as no move weighs less than its length times the scale
+#[derive(Default)]
+pub struct EuclideanHeuristic {
+	targets: Vec<(usize, usize)>,
+	scale: f64,
+}
+
+impl EuclideanHeuristic {
+	pub fn init(map: &Map<CellType>) -> Self {
+		EuclideanHeuristic {
+			targets: map.targets.to_owned(),
+			scale: 1.0,
+		}
+	}
+
+	/// Estimates the distance from the initial cell instead, for searching backwards from the targets
+	pub fn from_initial(map: &Map<CellType>) -> Self {
+		EuclideanHeuristic {
+			targets: vec![map.initial],
+			scale: 1.0,
+		}
+	}
+
+	/// Weigh a cell's length of straight line as `scale`, rather than 1
+	pub fn with_scale(mut self, scale: f64) -> Self {
+		self.scale = scale;
+		self
+	}
+}
+
+impl Heuristic for EuclideanHeuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.targets.iter()
+			.map(|(ref x, ref y)| {
+				let dx = (cursor.0.max(*x) - cursor.0.min(*x)) as f64;
+				let dy = (cursor.1.max(*y) - cursor.1.min(*y)) as f64;
+
+				(self.scale * dx.hypot(dy)).floor() as usize
+			})
+			.min() // Lead to the closer target
+			.expect("no targets found")
+	}
+}
+
+/// Adapter that multiplies the estimate of another heuristic, for counting moves in the units of a weigher
+pub struct Scaled<H> {
+	heuristic: H,
+	scale: usize,
+}
+
+impl<H> Scaled<H> where
+	H: Heuristic {
+	pub fn init(heuristic: H, scale: usize) -> Self {
+		Scaled {
+			heuristic,
+			scale,
+		}
+	}
+}
+
+impl<H> Heuristic for Scaled<H> where
+	H: Heuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.heuristic.estimate(cursor) * self.scale
+	}
+}
+
+/// Chebyshev-distance heuristic function, the most cells apart on either axis,
+/// which is the number of moves when every move weighs the same and diagonals are allowed
+///
+/// Hex moves also never go more than a cell along either axis, so this holds for them too.
+/// Moves that reach further, such as a knight's, need [`ChebyshevHeuristic::with_reach`].
+#[derive(Default)]
+pub struct ChebyshevHeuristic {
+	targets: Vec<(usize, usize)>,
+	/// Most cells a single move can cover along either axis
+	reach: usize,
+}
+
+impl ChebyshevHeuristic {
+	pub fn init(map: &Map<CellType>) -> Self {
+		ChebyshevHeuristic {
+			targets: map.targets.to_owned(),
+			reach: 1,
+		}
+	}
+
+	/// Estimates the distance from the initial cell instead, for searching backwards from the targets
+	pub fn from_initial(map: &Map<CellType>) -> Self {
+		ChebyshevHeuristic {
+			targets: vec![map.initial],
+			reach: 1,
+		}
+	}
+
+	/// Count moves that can cover up to `reach` cells along an axis, 2 for knight moves
+	pub fn with_reach(mut self, reach: usize) -> Self {
+		self.reach = reach;
+		self
+	}
+}
+
+impl Heuristic for ChebyshevHeuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.targets.iter()
+			.map(|(ref x, ref y)| {
+				let dx = cursor.0.max(*x) - cursor.0.min(*x);
+				let dy = cursor.1.max(*y) - cursor.1.min(*y);
+
+				dx.max(dy).div_ceil(self.reach)
+			})
+			.min() // Lead to the closer target
+			.expect("no targets found")
+	}
+}
+
+/// Manhattan-distance heuristic function, but adjusted such that it takes the variable-distance effects of [`CustomMoveWeight`]
+///
+/// The weights are the ones on the map, the same ones the search is weighed with, so it never overestimates whatever they are
 #[derive(Default)]
 pub struct CustomManhattan {
 	targets: Vec<(usize, usize)>,
+	weights: CustomMoveWeight,
+	/// Estimate travelling from the targets to the cursor rather than the other way around
+	backwards: bool,
 }
 
 impl CustomManhattan {
 	pub fn init(map: &Map<CellType>) -> Self {
 		CustomManhattan {
 			targets: map.targets.to_owned(),
+			weights: map.weights,
+			backwards: false,
+		}
+	}
+
+	/// Estimates the cost of getting from the initial cell to the cursor instead, for searching backwards from the targets
+	///
+	/// The moves are weighed the way they're made going forwards, so this isn't just the reverse of [`CustomManhattan::init`]
+	pub fn from_initial(map: &Map<CellType>) -> Self {
+		CustomManhattan {
+			targets: vec![map.initial],
+			weights: map.weights,
+			backwards: true,
 		}
 	}
 }
@@ -71,16 +274,22 @@ impl Heuristic for CustomManhattan {
 				x -= target_x;
 				y -= target_y;
 
+				if self.backwards {
+					x = -x;
+					y = -y;
+				}
+
+				// The target being behind the cursor on an axis means travelling backwards on it
 				let x_weight = if x < 0 {
-					CustomMoveWeight::weigh(&Direction::Right) * x.mul(-1) as usize
+					self.weights.left * x.mul(-1) as usize
 				} else {
-					CustomMoveWeight::weigh(&Direction::Left) * x as usize
+					self.weights.right * x as usize
 				};
 
 				let y_weight = if y < 0 {
-					CustomMoveWeight::weigh(&Direction::Down) * y.mul(-1) as usize
+					self.weights.up * y.mul(-1) as usize
 				} else {
-					CustomMoveWeight::weigh(&Direction::Up) * y as usize
+					self.weights.down * y as usize
 				};
 
 				x_weight + y_weight
@@ -89,3 +298,56 @@ impl Heuristic for CustomManhattan {
 			.expect("no targets found")
 	}
 }
+
+/// ALT heuristic, the best lower bound the triangle inequality gives through any of the [`Landmarks`]
+///
+/// The tables take walls and terrain into account, so it's a lot closer than the distance heuristics on maze-like maps.
+/// It never overestimates for the weigher the tables were worked out with.
+pub struct LandmarkHeuristic {
+	landmarks: Arc<Landmarks>,
+	targets: Vec<(usize, usize)>,
+	/// Estimate travelling from the initial cell to the cursor rather than from the cursor to the targets
+	backwards: bool,
+}
+
+impl LandmarkHeuristic {
+	/// Panics if the tables aren't the size of the map, see [`Landmarks::fits`] for checking they go with it
+	pub fn init(map: &Map<CellType>, landmarks: Arc<Landmarks>) -> Self {
+		assert!((landmarks.rows, landmarks.cols) == (map.rows, map.cols), "the landmark tables are for a {}x{} map", landmarks.rows, landmarks.cols);
+
+		LandmarkHeuristic {
+			landmarks,
+			targets: map.targets.to_owned(),
+			backwards: false,
+		}
+	}
+
+	/// Estimates the cost of getting from the initial cell to the cursor instead, for searching backwards from the targets
+	pub fn from_initial(map: &Map<CellType>, landmarks: Arc<Landmarks>) -> Self {
+		assert!((landmarks.rows, landmarks.cols) == (map.rows, map.cols), "the landmark tables are for a {}x{} map", landmarks.rows, landmarks.cols);
+
+		LandmarkHeuristic {
+			landmarks,
+			targets: vec![map.initial],
+			backwards: true,
+		}
+	}
+}
+
+impl Heuristic for LandmarkHeuristic {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.targets.iter()
+			.map(|target| {
+				if self.backwards { self.landmarks.bound(*target, cursor) } else { self.landmarks.bound(cursor, *target) }
+			})
+			.min() // Lead to the closer target
+			.expect("no targets found")
+	}
+}
+
+/// The exact cost, straight out of the field, for cells that can't get anywhere it's more than any path on the map
+impl Heuristic for DistanceField {
+	fn estimate(&self, cursor: (usize, usize)) -> usize {
+		self.get(cursor).unwrap_or(self.cut_off)
+	}
+}
@@ -0,0 +1,266 @@
+use crate::map::{Map, CellType};
+use crate::search::MoveWeight;
+use crate::utils::distances;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Number of landmarks picked when it's not up to the caller
+pub const DEFAULT_COUNT: usize = 8;
+
+/// How the landmarks are spread over the map
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Selection {
+	/// The open cells closest to the corners of the map, then to the middles of its edges, so no more than eight
+	Corners,
+	/// Each landmark as far as it gets from the ones before it, starting with the cell farthest from the initial one
+	#[default]
+	Farthest,
+}
+
+/// Costs of getting from a handful of landmark cells to every cell and back, for the ALT heuristic
+///
+/// The tables only hold for the walls, movement, terrain and move weights they were worked out with,
+/// but the initial cell and the targets can move around, so they can be saved and reused across searches on the same map
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Landmarks {
+	pub rows: usize,
+	pub cols: usize,
+	/// What the tables were worked out over, see [`fingerprint`]
+	pub fingerprint: u64,
+	cells: Vec<(usize, usize)>,
+	/// Cost from each landmark to every cell, keyed by [`Map::index`]
+	from: Vec<Vec<usize>>,
+	/// Cost from every cell to each landmark, keyed by [`Map::index`]
+	to: Vec<Vec<usize>>,
+}
+
+impl Landmarks {
+	/// Works out the tables for the given landmark cells
+	pub fn init<W: MoveWeight>(map: &Map<CellType>, weigher: &W, cells: Vec<(usize, usize)>) -> Landmarks {
+		let mut landmarks = Landmarks {
+			rows: map.rows,
+			cols: map.cols,
+			fingerprint: fingerprint(map, weigher),
+			cells: vec![],
+			from: vec![],
+			to: vec![],
+		};
+		cells.into_iter().for_each(|x| landmarks.push(map, weigher, x));
+
+		return landmarks;
+	}
+
+	/// Picks up to `count` landmarks out of the open cells of the map and works out their tables
+	pub fn select<W: MoveWeight>(map: &Map<CellType>, weigher: &W, count: usize, selection: Selection) -> Landmarks {
+		let mut landmarks = Landmarks::init(map, weigher, vec![]);
+
+		match selection {
+			Selection::Corners => {
+				let (right, bottom) = (map.cols - 1, map.rows - 1);
+				let anchors = [
+					(0, 0), (right, bottom), (right, 0), (0, bottom),
+					(right / 2, 0), (right / 2, bottom), (0, bottom / 2), (right, bottom / 2),
+				];
+
+				for anchor in anchors.iter().take(count) {
+					let closest = (0..map.rows * map.cols)
+						.map(|i| (i % map.cols, i / map.cols))
+						.filter(|x| !matches!(map.read_cell(*x), CellType::Wall(_)) && !landmarks.cells.contains(x))
+						.min_by_key(|(x, y)| x.abs_diff(anchor.0).pow(2) + y.abs_diff(anchor.1).pow(2));
+
+					if let Some(cell) = closest {
+						landmarks.push(map, weigher, cell);
+					}
+				}
+			}
+			Selection::Farthest => {
+				let reach = distances::from_sources(map, &[map.initial], weigher);
+				// Cost from the closest landmark so far, or from the initial cell before there are any
+				let mut nearest = reach.clone();
+
+				for _ in 0..count {
+					let farthest = (0..nearest.len())
+						.filter(|i| reach[*i] != usize::MAX)
+						.max_by_key(|i| nearest[*i])
+						.filter(|i| nearest[*i] > 0);
+
+					let i = match farthest {
+						Some(i) => i,
+						None => break
+					};
+					landmarks.push(map, weigher, (i % map.cols, i / map.cols));
+
+					let table = landmarks.from.last().expect("just pushed");
+					nearest.iter_mut().zip(table).for_each(|(x, cost)| *x = (*x).min(*cost));
+				}
+			}
+		}
+
+		return landmarks;
+	}
+
+	/// Adds a landmark and works out its tables
+	fn push<W: MoveWeight>(&mut self, map: &Map<CellType>, weigher: &W, cell: (usize, usize)) {
+		self.cells.push(cell);
+		self.from.push(distances::from_sources(map, &[cell], weigher));
+		self.to.push(distances::to_sinks(map, &[cell], weigher));
+	}
+
+	pub fn cells(&self) -> &[(usize, usize)] {
+		&self.cells
+	}
+
+	/// Whether the tables were worked out over the same walls, terrain and movement as the map, with the same move weights
+	pub fn fits<W: MoveWeight>(&self, map: &Map<CellType>, weigher: &W) -> bool {
+		self.rows == map.rows && self.cols == map.cols && self.fingerprint == fingerprint(map, weigher)
+	}
+
+	/// Lower bound on the cost of getting from `a` to `b`, by the triangle inequality through every landmark
+	pub fn bound(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+		let (a, b) = (a.0 + a.1 * self.cols, b.0 + b.1 * self.cols);
+
+		self.from.iter().zip(&self.to)
+			.map(|(from, to)| {
+				// a gets to the landmark no cheaper than going through b
+				let via_to = match (to[a], to[b]) {
+					(x, y) if x == usize::MAX || y == usize::MAX => 0,
+					(x, y) => x.saturating_sub(y)
+				};
+				// and the landmark gets to b no cheaper than going through a
+				let via_from = match (from[a], from[b]) {
+					(x, y) if x == usize::MAX || y == usize::MAX => 0,
+					(x, y) => y.saturating_sub(x)
+				};
+
+				via_to.max(via_from)
+			})
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Serializes the tables and writes them to a file, see [`Landmarks::from_str`] to read them back
+	pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+		let mut handle = OpenOptions::new()
+			.create_new(true)
+			.write(true)
+			.open(path)?;
+
+		write!(handle, "{}", self)?;
+
+		Ok(())
+	}
+}
+
+/// Writes out the size of the map like a map file does, its fingerprint and the landmark cells,
+/// then a line of costs from and a line of costs to each landmark, with `-` for the cells that can't be reached
+impl Display for Landmarks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let table = |costs: &Vec<usize>| -> String {
+			costs.iter()
+				.map(|x| if *x == usize::MAX { "-".to_owned() } else { x.to_string() })
+				.collect::<Vec<String>>()
+				.join(" ")
+		};
+
+		writeln!(f, "[{}, {}]", self.rows, self.cols)?;
+		writeln!(f, "{:016x}", self.fingerprint)?;
+		writeln!(f, "{}", self.cells.iter()
+			.map(|(x, y)| format!("({}, {})", x, y))
+			.collect::<Vec<String>>()
+			.join(" | "))?;
+
+		for (from, to) in self.from.iter().zip(&self.to) {
+			writeln!(f, "{}", table(from))?;
+			writeln!(f, "{}", table(to))?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Hash of everything the tables depend on: the walls, terrain and movement of the map, and what the weigher makes of its moves
+///
+/// It's FNV-1a rather than the hasher of the standard library, which is free to change between releases, so the files stay good
+pub fn fingerprint<W: MoveWeight>(map: &Map<CellType>, weigher: &W) -> u64 {
+	let mut hash: u64 = 0xcbf29ce484222325;
+	let mut feed = |x: usize| {
+		for byte in (x as u64).to_le_bytes() {
+			hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+		}
+	};
+
+	feed(map.rows);
+	feed(map.cols);
+	for y in 0..map.rows {
+		for x in 0..map.cols {
+			feed(matches!(map.read_cell((x, y)), CellType::Wall(_)) as usize);
+			feed(map.terrain((x, y)));
+		}
+	}
+	format!("{:?}", map.movement).bytes().for_each(|x| feed(x as usize));
+	for direction in map.movement.directions() {
+		feed(weigher.weigh(direction));
+	}
+
+	return hash;
+}
+
+/// Pulls the numbers out of a line like `[2, 3]` or `(4, 5)`
+fn pair(line: &str) -> Result<(usize, usize), Box<dyn Error>> {
+	let numbers = line.trim()
+		.trim_matches(|x| matches!(x, '[' | ']' | '(' | ')'))
+		.split(',')
+		.map(|x| x.trim().parse::<usize>())
+		.collect::<Result<Vec<usize>, _>>()?;
+
+	match numbers[..] {
+		[a, b] => Ok((a, b)),
+		_ => Err(format!("expected a pair of numbers, got {}", line).into())
+	}
+}
+
+impl FromStr for Landmarks {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut lines = s.lines();
+		let (rows, cols) = pair(lines.next().ok_or("missing map size")?)?;
+		let size = rows.checked_mul(cols).ok_or_else(|| format!("a {} by {} map is too big", rows, cols))?;
+		let fingerprint = u64::from_str_radix(lines.next().ok_or("missing the fingerprint of the map")?.trim(), 16)
+			.map_err(|_| "the fingerprint of the map isn't a hexadecimal number")?;
+
+		let cells = lines.next().ok_or("missing landmark cells")?;
+		let cells = cells.split('|')
+			.filter(|x| !x.trim().is_empty())
+			.map(pair)
+			.collect::<Result<Vec<(usize, usize)>, _>>()?;
+
+		let mut tables = lines
+			.map(|line| {
+				let table = line.split_whitespace()
+					.map(|x| if x == "-" { Ok(usize::MAX) } else { x.parse::<usize>() })
+					.collect::<Result<Vec<usize>, _>>()?;
+
+				if table.len() != size {
+					return Err(format!("expected {} costs in a table, got {}", size, table.len()).into());
+				}
+				Ok(table)
+			})
+			.collect::<Result<Vec<Vec<usize>>, Box<dyn Error>>>()?
+			.into_iter();
+
+		let mut landmarks = Landmarks { rows, cols, fingerprint, cells, from: vec![], to: vec![] };
+		for _ in 0..landmarks.cells.len() {
+			landmarks.from.push(tables.next().ok_or("missing the costs from a landmark")?);
+			landmarks.to.push(tables.next().ok_or("missing the costs to a landmark")?);
+		}
+		if tables.next().is_some() {
+			return Err(format!("more tables than the 2 for each of the {} landmarks", landmarks.cells.len()).into());
+		}
+
+		Ok(landmarks)
+	}
+}
@@ -1,3 +1,10 @@
 pub mod heuristics;
+pub mod distances;
+pub mod landmarks;
+pub mod check;
 pub mod queue;
-pub mod filter;
\ No newline at end of file
+pub mod filter;
+pub mod visited;
+
+#[cfg(test)]
+mod tests;
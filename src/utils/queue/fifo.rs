@@ -1,4 +1,3 @@
-use std::mem;
 use crate::utils::queue::QueueStrategy;
 
 /// A queue that's sorted from first to last
@@ -19,10 +18,14 @@ impl<T> QueueStrategy<T> for FIFOQueue<T> {
 			return None;
 		}
 
-		let shifted = mem::replace(&mut self.buffer[self.index], None);
+		let shifted = self.buffer[self.index].take();
 		self.index += 1;
 		return shifted;
 	}
+
+	fn len(&self) -> usize {
+		self.buffer.len() - self.index
+	}
 }
 
 impl FIFOQueue<()> {
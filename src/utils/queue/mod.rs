@@ -1,6 +1,6 @@
 pub mod sorted;
 pub mod fifo;
-#[cfg(test)]
+#[cfg(all(test, feature = "nightly"))]
 mod bench;
 
 /// A queue adapter which allows for alternative queueing strategies such as FIFO
@@ -8,4 +8,11 @@ pub trait QueueStrategy<T> {
 	fn queue(&mut self, item: T);
 
 	fn dequeue(&mut self) -> Option<T>;
+
+	/// Number of items waiting in the queue
+	fn len(&self) -> usize;
+
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
 }
\ No newline at end of file
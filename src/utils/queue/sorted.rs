@@ -59,6 +59,18 @@ impl<T> QueueStrategy<T> for SortedQueue<T> where
 		}
 		None
 	}
+
+	fn len(&self) -> usize {
+		self.buffer.len()
+	}
+}
+
+impl<T> SortedQueue<T> where
+	T: Weighted {
+	/// The lightest-weighed object, without taking it off the queue
+	pub fn peek(&self) -> Option<&T> {
+		self.buffer.peek().map(|x| &x.inner)
+	}
 }
 
 impl SortedQueue<()> {
@@ -75,12 +87,14 @@ pub struct VecBackedSortedQueue<T> {
 	buffer: Vec<T>,
 	pub tx: Sender<T>,
 	rx: Receiver<T>,
+	len: usize,
 }
 
 #[cfg(test)]
 impl<T> QueueStrategy<T> for VecBackedSortedQueue<T> where
 	T: Weighted {
 	fn queue(&mut self, t: T) {
+		self.len += 1;
 		self.tx.send(t).expect("broken queue")
 	}
 
@@ -93,7 +107,15 @@ impl<T> QueueStrategy<T> for VecBackedSortedQueue<T> where
 			})
 		}
 
-		return self.buffer.pop();
+		let popped = self.buffer.pop();
+		if popped.is_some() {
+			self.len -= 1;
+		}
+		return popped;
+	}
+
+	fn len(&self) -> usize {
+		self.len
 	}
 }
 
@@ -106,6 +128,7 @@ impl VecBackedSortedQueue<()> {
 			buffer: vec![],
 			tx,
 			rx,
+			len: 0,
 		}
 	}
 }
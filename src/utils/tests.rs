@@ -0,0 +1,191 @@
+mod distances {
+	use crate::map::{Map, CellType};
+	use crate::map::movement::{Movement, Corners};
+	use crate::search::CustomMoveWeight;
+	use crate::utils::distances::{from_sources, to_sinks};
+
+	#[test]
+	fn backwards_matches_forwards() {
+		let weights = CustomMoveWeight { up: 1, left: 7, down: 2, right: 3 };
+
+		for movement in [Movement::FourWay, Movement::EightWay(Corners::NoSqueeze), Movement::Knight, Movement::Hex] {
+			let mut map = Map::random_maze(8, 12, 1);
+			map.movement = movement;
+			map.scatter_terrain(3, 4);
+
+			let sink = map.targets[0];
+			let to = to_sinks(&map, &[sink], &weights);
+			for (i, cost) in to.iter().enumerate() {
+				let cell = (i % map.cols, i / map.cols);
+				let expected = if let CellType::Wall(_) = map.read_cell(cell) { usize::MAX } else { from_sources(&map, &[cell], &weights)[map.index(sink)] };
+				assert_eq!(*cost, expected, "{:?} from {:?}", movement, cell);
+			}
+		}
+	}
+}
+
+mod landmarks {
+	use crate::map::{Map, CellType};
+	use crate::search::{UniformMoveWeight, CustomMoveWeight};
+	use crate::utils::distances::from_sources;
+	use crate::utils::heuristics::{Heuristic, LandmarkHeuristic};
+	use crate::utils::landmarks::{Landmarks, Selection};
+	use std::str::FromStr;
+	use std::sync::Arc;
+
+	#[test]
+	fn never_overestimates() {
+		for selection in [Selection::Corners, Selection::Farthest] {
+			for _ in 0..10 {
+				let map = Map::random_maze(16, 24, 1);
+				let landmarks = Landmarks::select(&map, &UniformMoveWeight, 4, selection);
+				assert_eq!(landmarks.cells().len(), 4);
+
+				// Moves weigh the same both ways, so the cost from the target is the cost to it
+				let exact = from_sources(&map, &map.targets, &UniformMoveWeight);
+				let heuristic = LandmarkHeuristic::init(&map, Arc::new(landmarks));
+				for i in (0..exact.len()).filter(|i| exact[*i] != usize::MAX) {
+					assert!(heuristic.estimate((i % map.cols, i / map.cols)) <= exact[i]);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn tables_read_back_the_way_they_are_written() {
+		let map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(3, 2)\n(1, 0, 1, 2)\n(3, 0, 1, 1)").unwrap();
+		let landmarks = Landmarks::select(&map, &UniformMoveWeight, 2, Selection::Farthest);
+
+		assert_eq!(Landmarks::from_str(&landmarks.to_string()).unwrap(), landmarks);
+		assert!(Landmarks::from_str("[3, 4]\n00000000000000ff\n(0, 0)\n0 1 2").is_err());
+		assert!(Landmarks::from_str("[3, 4]\n(0, 0)\n0 1 2 3 4 5 6 7 8 9 10 11").is_err());
+		assert!(Landmarks::from_str("[99999999999, 99999999999]\n0000000000000000\n(0, 0)\n1\n1").is_err());
+
+		// Tables past the two of each landmark
+		let text = landmarks.to_string();
+		assert!(Landmarks::from_str(&format!("{}{}", text, text.lines().last().unwrap())).is_err());
+	}
+
+	#[test]
+	fn tables_only_fit_the_map_they_were_worked_out_on() {
+		let mut map: Map<CellType> = Map::from_str("[3, 4]\n(0, 0)\n(3, 2)\n(1, 0, 1, 2)\n(3, 0, 1, 1)").unwrap();
+		let landmarks = Landmarks::select(&map, &UniformMoveWeight, 2, Selection::Farthest);
+		assert!(landmarks.fits(&map, &UniformMoveWeight));
+
+		// The initial cell and the targets can move around
+		assert!(landmarks.fits(&map.between((0, 2), (2, 0)), &UniformMoveWeight));
+		assert!(!landmarks.fits(&map, &CustomMoveWeight::default()));
+
+		let mut walled = map.clone();
+		*walled.read_cell_mut((2, 2)) = CellType::Wall(false);
+		assert!(!landmarks.fits(&walled, &UniformMoveWeight));
+
+		map.set_terrain((2, 2), 3);
+		assert!(!landmarks.fits(&map, &UniformMoveWeight));
+	}
+}
+
+mod distance_field {
+	use crate::map::Map;
+	use crate::runner::{Method, HeuristicKind};
+	use crate::search::{Search, MoveWeight, CustomMoveWeight};
+	use crate::utils::distances::{DistanceField, from_sources};
+
+	#[test]
+	fn cheapest_to_the_nearest_target() {
+		for _ in 0..5 {
+			let mut map = Map::random_maze(10, 14, 3);
+			map.weights = CustomMoveWeight { up: 1, left: 7, down: 2, right: 3 };
+			let field = DistanceField::init(&map, &map.weights);
+
+			for (i, cost) in field.costs().iter().enumerate() {
+				let from = from_sources(&map, &[(i % map.cols, i / map.cols)], &map.weights);
+				let nearest = map.targets.iter().map(|x| from[map.index(*x)]).min().unwrap();
+				if *cost != usize::MAX {
+					assert_eq!(*cost, nearest);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn followed_downhill_to_a_target() {
+		for _ in 0..10 {
+			let mut map = Map::random_maze(16, 24, 2);
+			map.weights = CustomMoveWeight { up: 1, left: 7, down: 2, right: 3 };
+			let field = DistanceField::init(&map, &map.weights);
+
+			let (mut cur, mut path) = (map.initial, vec![]);
+			while let Some((dir, next)) = field.next_move(&map, &map.weights, cur) {
+				path.push(dir);
+				cur = next;
+			}
+
+			match field.get(map.initial) {
+				Some(cost) => {
+					assert!(map.targets.contains(&cur));
+					assert_eq!(map.path_cost(&map.weights, &path), cost);
+				}
+				None => assert!(path.is_empty())
+			}
+		}
+	}
+
+	#[test]
+	fn a_star_stays_on_cheapest_paths() {
+		for _ in 0..10 {
+			let map = Map::random_maze(16, 24, 2);
+			let field = DistanceField::init(&map, &map.weights);
+			let travelled = from_sources(&map, &[map.initial], &map.weights);
+
+			let mut search = Method::AStar.build_with(&map, true, HeuristicKind::Exact);
+			search.record_expansions(true);
+			let report = search.search();
+
+			assert_eq!(report.cost, field.get(map.initial));
+			if let Some(cost) = report.cost {
+				for cell in report.expanded_cells.unwrap() {
+					assert_eq!(travelled[map.index(cell)] + field.get(cell).unwrap(), cost, "{:?} is off the cheapest paths", cell);
+				}
+				assert!(report.solution.map(|x| map.weights.path_cost(&x)) == Some(cost));
+			}
+		}
+	}
+}
+
+mod check {
+	use crate::map::Map;
+	use crate::map::movement::{Movement, Corners};
+	use crate::search::{CustomMoveWeight, EuclideanMoveWeight};
+	use crate::utils::check::check_heuristic;
+	use crate::utils::distances::DistanceField;
+	use crate::utils::heuristics::{CustomManhattan, ManhattanHeuristic, Scaled};
+
+	#[test]
+	fn catches_stale_weights() {
+		let mut map = Map::random_maze(16, 24, 1);
+		let heuristic = CustomManhattan::init(&map);
+		assert!(check_heuristic(&map, &heuristic, &map.weights).admissible());
+
+		// The heuristic was built before the weights went down
+		map.weights = CustomMoveWeight { up: 1, left: 1, down: 1, right: 1 };
+		let check = check_heuristic(&map, &heuristic, &map.weights);
+		assert!(!check.admissible() && !check.consistent());
+		for x in check.overestimates {
+			assert!(x.estimate > x.cost);
+		}
+	}
+
+	#[test]
+	fn straight_moves_overestimate_diagonals() {
+		let mut map = Map::random_maze(16, 24, 1);
+		map.movement = Movement::EightWay(Corners::Cut);
+
+		let manhattan = Scaled::init(ManhattanHeuristic::init(&map), EuclideanMoveWeight::STRAIGHT);
+		assert!(!check_heuristic(&map, &manhattan, &EuclideanMoveWeight).consistent());
+
+		let exact = DistanceField::init(&map, &EuclideanMoveWeight);
+		let check = check_heuristic(&map, &exact, &EuclideanMoveWeight);
+		assert!(check.admissible() && check.consistent());
+	}
+}
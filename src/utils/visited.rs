@@ -0,0 +1,79 @@
+use crate::map::Map;
+
+/// Bitset of the cells a search has visited, keyed by [`Map::index`]
+///
+/// Searches keep their closed set here instead of writing onto the [`Map`], so that one map can be shared between many searches
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Visited {
+	cols: usize,
+	bits: Vec<u64>,
+}
+
+impl Visited {
+	/// Creates an empty set large enough to hold every cell of the map
+	pub fn init<Tag>(map: &Map<Tag>) -> Visited {
+		Visited {
+			cols: map.cols,
+			bits: vec![0; (map.rows * map.cols).div_ceil(64)],
+		}
+	}
+
+	fn index(&self, (x, y): (usize, usize)) -> (usize, u64) {
+		let i = x + y * self.cols;
+		(i / 64, 1 << (i % 64))
+	}
+
+	/// Checks if the cell has been visited
+	pub fn contains(&self, coords: (usize, usize)) -> bool {
+		let (word, mask) = self.index(coords);
+		self.bits[word] & mask != 0
+	}
+
+	/// Marks the cell as visited, returns `false` if it had already been visited
+	pub fn insert(&mut self, coords: (usize, usize)) -> bool {
+		let (word, mask) = self.index(coords);
+		let fresh = self.bits[word] & mask == 0;
+		self.bits[word] |= mask;
+		return fresh;
+	}
+
+	/// Unmarks the cell, returns `false` if it hadn't been visited
+	pub fn remove(&mut self, coords: (usize, usize)) -> bool {
+		let (word, mask) = self.index(coords);
+		let present = self.bits[word] & mask != 0;
+		self.bits[word] &= !mask;
+		return present;
+	}
+
+	/// Marks every cell that's visited in `other` as visited in this set too
+	pub fn union(&mut self, other: &Visited) {
+		self.bits.iter_mut()
+			.zip(other.bits.iter())
+			.for_each(|(this, other)| *this |= *other);
+	}
+
+	/// Forgets all visits
+	pub fn clear(&mut self) {
+		self.bits.iter_mut().for_each(|x| *x = 0);
+	}
+
+	/// Number of cells visited
+	pub fn len(&self) -> usize {
+		self.bits.iter().map(|x| x.count_ones() as usize).sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bits.iter().all(|x| *x == 0)
+	}
+
+	/// Iterates the coordinates of the visited cells in [`Map::index`] order
+	pub fn iter(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+		let cols = self.cols;
+		self.bits.iter()
+			.enumerate()
+			.flat_map(|(word, bits)| (0..64)
+				.filter(move |bit| bits & (1 << bit) != 0)
+				.map(move |bit| word * 64 + bit))
+			.map(move |i| (i % cols, i / cols))
+	}
+}